@@ -0,0 +1,271 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+
+use crate::{square::Square, types::Colour};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::sq;
+
+    fn names(bitboard: Bitboard) -> Vec<String> {
+        bitboard.squares().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_squares() {
+        let bitboard = Bitboard::from_square(sq("h8")) | Bitboard::from_square(sq("a1"));
+
+        assert_eq!(names(bitboard), ["a1", "h8"]);
+        assert_eq!(bitboard.count(), 2);
+        assert_eq!(bitboard.first(), Some(sq("a1")));
+        assert!(bitboard.contains(sq("h8")) && !bitboard.contains(sq("g8")));
+        assert!(Bitboard::EMPTY.is_empty());
+        assert!(
+            Bitboard::DARK_SQUARES.contains(sq("a1")) && Bitboard::DARK_SQUARES.contains(sq("h8"))
+        );
+        assert_eq!(Bitboard::DARK_SQUARES.count(), 32);
+    }
+
+    #[test]
+    fn test_step_attacks() {
+        assert_eq!(names(knight_attacks(sq("a1"))), ["c2", "b3"]);
+        assert_eq!(knight_attacks(sq("e4")).count(), 8);
+        assert_eq!(names(king_attacks(sq("h8"))), ["g7", "h7", "g8"]);
+        assert_eq!(names(pawn_attacks(sq("a2"), Colour::White)), ["b3"]);
+        assert_eq!(names(pawn_attacks(sq("e5"), Colour::Black)), ["d4", "f4"]);
+    }
+
+    #[test]
+    fn test_sliding_attacks() {
+        let occupied = Bitboard::from_square(sq("d6")) | Bitboard::from_square(sq("f4"));
+
+        assert_eq!(
+            names(rook_attacks(sq("d4"), occupied)),
+            ["d1", "d2", "d3", "a4", "b4", "c4", "e4", "f4", "d5", "d6"]
+        );
+        assert_eq!(bishop_attacks(sq("a1"), Bitboard::EMPTY).count(), 7);
+        assert_eq!(queen_attacks(sq("d4"), Bitboard::EMPTY).count(), 27);
+    }
+}
+
+/// A set of squares, one bit per square in the order of `Square::index`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Self = Self(0);
+    /// a1, c1, ..., b2, d2, ... and the other squares of a1's colour.
+    pub const DARK_SQUARES: Self = Self(0xAA55_AA55_AA55_AA55);
+
+    pub fn from_square(square: Square) -> Self {
+        Self(1 << square.index())
+    }
+
+    pub fn contains(self, square: Square) -> bool {
+        self.0 & (1 << square.index()) != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// The lowest square in the set.
+    pub fn first(self) -> Option<Square> {
+        Square::from_index(self.0.trailing_zeros() as usize)
+    }
+
+    /// The squares in the set, from a1 to h8.
+    pub fn squares(self) -> impl Iterator<Item = Square> {
+        let mut bits = self.0;
+
+        std::iter::from_fn(move || {
+            let square = Self(bits).first()?;
+            bits &= bits - 1;
+            Some(square)
+        })
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+const KNIGHT_OFFSETS: [[i8; 2]; 8] = [
+    [1, 2],
+    [2, 1],
+    [2, -1],
+    [1, -2],
+    [-1, -2],
+    [-2, -1],
+    [-2, 1],
+    [-1, 2],
+];
+const KING_OFFSETS: [[i8; 2]; 8] = [
+    [0, 1],
+    [1, 1],
+    [1, 0],
+    [1, -1],
+    [0, -1],
+    [-1, -1],
+    [-1, 0],
+    [-1, 1],
+];
+
+/// The eight ray directions. The first four run towards higher square indices.
+const DIRECTIONS: [[i8; 2]; 8] = [
+    [0, 1],
+    [1, 1],
+    [1, 0],
+    [-1, 1],
+    [0, -1],
+    [-1, -1],
+    [-1, 0],
+    [1, -1],
+];
+const ROOK_DIRECTIONS: [usize; 4] = [0, 2, 4, 6];
+const BISHOP_DIRECTIONS: [usize; 4] = [1, 3, 5, 7];
+
+static KNIGHT_ATTACKS: [u64; 64] = step_table(&KNIGHT_OFFSETS);
+static KING_ATTACKS: [u64; 64] = step_table(&KING_OFFSETS);
+static PAWN_ATTACKS: [[u64; 64]; 2] = [
+    step_table(&[[-1, 1], [1, 1]]),
+    step_table(&[[-1, -1], [1, -1]]),
+];
+static RAYS: [[u64; 64]; 8] = ray_table();
+
+/// The bit of the square `files` and `ranks` away from `square`, or 0 off the board.
+const fn bit_at(square: usize, files: i8, ranks: i8) -> u64 {
+    let file = (square % 8) as i8 + files;
+    let rank = (square / 8) as i8 + ranks;
+
+    if file < 0 || file > 7 || rank < 0 || rank > 7 {
+        0
+    } else {
+        1 << (rank * 8 + file)
+    }
+}
+
+const fn step_table(offsets: &[[i8; 2]]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+
+    while square < 64 {
+        let mut i = 0;
+        while i < offsets.len() {
+            table[square] |= bit_at(square, offsets[i][0], offsets[i][1]);
+            i += 1;
+        }
+        square += 1;
+    }
+
+    table
+}
+
+const fn ray_table() -> [[u64; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+
+    while direction < 8 {
+        let [files, ranks] = DIRECTIONS[direction];
+        let mut square = 0;
+
+        while square < 64 {
+            let mut distance = 1;
+            while distance < 8 {
+                table[direction][square] |= bit_at(square, files * distance, ranks * distance);
+                distance += 1;
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+
+    table
+}
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    Bitboard(KNIGHT_ATTACKS[square.index()])
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    Bitboard(KING_ATTACKS[square.index()])
+}
+
+/// The squares a pawn of `colour` on `square` captures on.
+pub fn pawn_attacks(square: Square, colour: Colour) -> Bitboard {
+    Bitboard(PAWN_ATTACKS[colour.index()][square.index()])
+}
+
+/// The squares along `direction` up to and including the first occupied one.
+fn ray_attacks(direction: usize, square: Square, occupied: Bitboard) -> u64 {
+    let ray = RAYS[direction][square.index()];
+    let blockers = ray & occupied.0;
+
+    if blockers == 0 {
+        return ray;
+    }
+
+    let blocker = if direction < 4 {
+        blockers.trailing_zeros()
+    } else {
+        63 - blockers.leading_zeros()
+    };
+
+    ray ^ RAYS[direction][blocker as usize]
+}
+
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    Bitboard(
+        ROOK_DIRECTIONS
+            .iter()
+            .fold(0, |acc, d| acc | ray_attacks(*d, square, occupied)),
+    )
+}
+
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    Bitboard(
+        BISHOP_DIRECTIONS
+            .iter()
+            .fold(0, |acc, d| acc | ray_attacks(*d, square, occupied)),
+    )
+}
+
+pub fn queen_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    rook_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
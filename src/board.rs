@@ -1,13 +1,22 @@
 use std::fmt::Display;
 
 use crate::{
+    bitboard::Bitboard,
+    diagram::DiagramStyle,
     piece::Piece,
-    types::{Colour, PieceType},
+    square::{File, Rank, Square},
+    types::{CastlingRights, Colour, PieceType},
+    zobrist::piece_key,
 };
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::square::sq;
+
+    fn squares(names: &[&str]) -> Vec<Square> {
+        names.iter().map(|n| sq(n)).collect()
+    }
 
     #[test]
     fn test_equality() {
@@ -19,48 +28,75 @@ mod tests {
 
     #[test]
     fn test_find_piece() {
-        let mut start_board = Board::default();
+        let start_board = Board::default();
+
+        assert_eq!(start_board.piece_at(sq("a1")), Some(PieceType::Rook(Colour::White)));
+        assert_eq!(start_board.piece_at(sq("e8")), Some(PieceType::King(Colour::Black)));
+        assert!(start_board.piece_at(sq("d4")).is_none());
+        assert_eq!(start_board.pieces().count(), 32);
+        assert_eq!(
+            start_board.pieces().next(),
+            Some(Piece::new(sq("a1"), PieceType::Rook(Colour::White)))
+        );
+    }
 
-        assert_eq!(start_board.find_piece_by_pos(0, 0).unwrap(), 0);
-        assert_eq!(start_board.find_piece_by_pos(7, 0).unwrap(), 1);
-        assert!(start_board.find_piece_by_pos(3, 3).is_none());
+    #[test]
+    fn test_put_and_remove() {
+        let mut board = Board::_blank();
+
+        board.put_piece(sq("e4"), PieceType::Knight(Colour::White));
+        board.put_piece(sq("e4"), PieceType::Queen(Colour::Black));
+        assert_eq!(board.piece_at(sq("e4")), Some(PieceType::Queen(Colour::Black)));
+        assert_eq!(board.pieces().count(), 1);
+
+        board.move_piece(sq("e4"), sq("a8"));
+        assert_eq!(
+            board.bitboard(PieceType::Queen(Colour::Black)),
+            Bitboard::from_square(sq("a8"))
+        );
+        assert_eq!(board.remove_piece(sq("a8")), Some(PieceType::Queen(Colour::Black)));
+        assert!(board.occupied().is_empty());
     }
 
     #[test]
     fn test_tiles_between() {
-        let q_to_q = Board::get_tiles_between([3, 0], [3, 7]).unwrap();
+        let q_to_q = Board::get_tiles_between(sq("d1"), sq("d8")).unwrap();
 
-        let expected_result = vec![[3, 1], [3, 2], [3, 3], [3, 4], [3, 5], [3, 6]];
+        let expected_result = squares(&["d2", "d3", "d4", "d5", "d6", "d7"]);
 
         assert_eq!(q_to_q, expected_result);
 
-        let highly_spesific_case = Board::get_tiles_between([3, 0], [7, 0]).unwrap();
-        let expected_result = vec![[4, 0], [5, 0], [6, 0]];
+        let highly_spesific_case = Board::get_tiles_between(sq("d1"), sq("h1")).unwrap();
+        let expected_result = squares(&["e1", "f1", "g1"]);
 
         assert_eq!(highly_spesific_case, expected_result);
 
-        let negative_test = Board::get_tiles_between([7,0], [3,0]).unwrap();
-        let expected_result = vec![[6, 0], [5, 0], [4, 0]];
+        let negative_test = Board::get_tiles_between(sq("h1"), sq("d1")).unwrap();
+        let expected_result = squares(&["g1", "f1", "e1"]);
 
         assert_eq!(negative_test, expected_result);
+
+        let diagonal = Board::get_tiles_between(sq("a1"), sq("d4")).unwrap();
+        assert_eq!(diagonal, squares(&["b2", "c3"]));
+        assert!(Board::get_tiles_between(sq("a1"), sq("b3")).is_none());
     }
 
     #[test]
     fn test_intervention() {
-        let mut initial_board = Board::default();
-        let r_to_r = Board::get_tiles_between([0,0], [7,0]).unwrap();
+        let initial_board = Board::default();
+        let r_to_r = Board::get_tiles_between(sq("a1"), sq("h1")).unwrap();
         let interventions = initial_board.get_intervening_pieces(&r_to_r);
 
         assert_eq!(interventions.len(), 6);
 
         let mut empty_board = Board::_blank();
 
-        empty_board.pieces.push(Piece::new([7,0], PieceType::Rook(Colour::White)));
-        empty_board.pieces.push(Piece::new([5,0], PieceType::Bishop(Colour::Black)));
-        empty_board.pieces.push(Piece::new([3, 4], PieceType::Rook(Colour::White)));
+        empty_board.put_piece(sq("h1"), PieceType::Rook(Colour::White));
+        empty_board.put_piece(sq("f1"), PieceType::Bishop(Colour::Black));
+        empty_board.put_piece(sq("d5"), PieceType::Rook(Colour::White));
 
-        let wrong_rook = Board::get_tiles_between([7,0], [3,0]).unwrap();
-        let correct_rook = Board::get_tiles_between([3,4], [3,0]).unwrap();
+        let wrong_rook = Board::get_tiles_between(sq("h1"), sq("d1")).unwrap();
+        let correct_rook = Board::get_tiles_between(sq("d5"), sq("d1")).unwrap();
 
         let i1 = empty_board.get_intervening_pieces(&wrong_rook);
         assert_eq!(i1.len(), 1);
@@ -72,149 +108,284 @@ mod tests {
 
 #[derive(Debug, Clone)]
 pub struct Board {
-    pub pieces: Vec<Piece>,
+    /// Pawns, knights, bishops, rooks, queens and kings of both colours.
+    by_kind: [Bitboard; 6],
+    by_colour: [Bitboard; 2],
+    /// The Zobrist key of the pieces alone, see `Board::zobrist`.
+    pub(crate) piece_key: u64,
+    /// The Zobrist keys of the positions before each move made on this board.
+    pub(crate) history: Vec<u64>,
+    pub side_to_move: Colour,
+    pub castling: CastlingRights,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
 }
 
+/// Boards are equal when the same pieces stand on the same squares.
 impl PartialEq for Board {
     fn eq(&self, other: &Self) -> bool {
-        let mut a = self.pieces.clone();
-        let mut b = other.pieces.clone();
-        a.sort() == b.sort()
+        self.by_kind == other.by_kind && self.by_colour == other.by_colour
     }
 }
 
 impl Default for Board {
     fn default() -> Self {
-        let mut pieces = vec![];
-
-        pieces.push(Piece::new([0, 0], PieceType::Rook(Colour::White)));
-        pieces.push(Piece::new([7, 0], PieceType::Rook(Colour::White)));
-        pieces.push(Piece::new([0, 7], PieceType::Rook(Colour::Black)));
-        pieces.push(Piece::new([7, 7], PieceType::Rook(Colour::Black)));
-
-        pieces.push(Piece::new([1, 0], PieceType::Knight(Colour::White)));
-        pieces.push(Piece::new([6, 0], PieceType::Knight(Colour::White)));
-        pieces.push(Piece::new([1, 7], PieceType::Knight(Colour::Black)));
-        pieces.push(Piece::new([6, 7], PieceType::Knight(Colour::Black)));
-
-        pieces.push(Piece::new([2, 0], PieceType::Bishop(Colour::White)));
-        pieces.push(Piece::new([5, 0], PieceType::Bishop(Colour::White)));
-        pieces.push(Piece::new([2, 7], PieceType::Bishop(Colour::Black)));
-        pieces.push(Piece::new([5, 7], PieceType::Bishop(Colour::Black)));
-
-        pieces.push(Piece::new([3, 0], PieceType::Queen(Colour::White)));
-        pieces.push(Piece::new([3, 7], PieceType::Queen(Colour::Black)));
-
-        pieces.push(Piece::new([4, 0], PieceType::King(Colour::White)));
-        pieces.push(Piece::new([4, 7], PieceType::King(Colour::Black)));
+        let white = |file| Square::new(file, Rank::FIRST);
+        let black = |file| Square::new(file, Rank::EIGHTH);
+
+        let mut pieces = vec![
+            Piece::new(white(File::A), PieceType::Rook(Colour::White)),
+            Piece::new(white(File::H), PieceType::Rook(Colour::White)),
+            Piece::new(black(File::A), PieceType::Rook(Colour::Black)),
+            Piece::new(black(File::H), PieceType::Rook(Colour::Black)),
+        ];
+
+        pieces.push(Piece::new(white(File::B), PieceType::Knight(Colour::White)));
+        pieces.push(Piece::new(white(File::G), PieceType::Knight(Colour::White)));
+        pieces.push(Piece::new(black(File::B), PieceType::Knight(Colour::Black)));
+        pieces.push(Piece::new(black(File::G), PieceType::Knight(Colour::Black)));
+
+        pieces.push(Piece::new(white(File::C), PieceType::Bishop(Colour::White)));
+        pieces.push(Piece::new(white(File::F), PieceType::Bishop(Colour::White)));
+        pieces.push(Piece::new(black(File::C), PieceType::Bishop(Colour::Black)));
+        pieces.push(Piece::new(black(File::F), PieceType::Bishop(Colour::Black)));
+
+        pieces.push(Piece::new(white(File::D), PieceType::Queen(Colour::White)));
+        pieces.push(Piece::new(black(File::D), PieceType::Queen(Colour::Black)));
+
+        pieces.push(Piece::new(white(File::E), PieceType::King(Colour::White)));
+        pieces.push(Piece::new(black(File::E), PieceType::King(Colour::Black)));
+
+        for file in (0..8).filter_map(File::new) {
+            let white_pawn = Square::new(file, Rank::SECOND);
+            let black_pawn = Square::new(file, Rank::SEVENTH);
+
+            pieces.push(Piece::new(white_pawn, PieceType::Pawn(Colour::White)));
+            pieces.push(Piece::new(black_pawn, PieceType::Pawn(Colour::Black)));
+        }
 
-        for i in 0..8 {
-            pieces.push(Piece::new([i, 1], PieceType::Pawn(Colour::White)));
-            pieces.push(Piece::new([i, 6], PieceType::Pawn(Colour::Black)));
+        let mut board = Self {
+            castling: CastlingRights::all(),
+            ..Self::_blank()
+        };
+        for piece in pieces {
+            board.put_piece(piece.pos, piece.piece_type);
         }
 
-        Self { pieces }
+        board
     }
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        
-        let mut board_1d: [PieceType; 64] = [PieceType::Empty(Colour::White); 64];
-        
-        for i in 0..board_1d.len() {
-            board_1d[i] = match self
-            .pieces
-            .iter()
-            .filter(|x| x.pos == [(i % 8) as i8, (i as f64 / 8.0).floor() as i8])
-            .nth(0)
-            {
-                Some(p) => p.piece_type,
-                None => PieceType::Empty(Colour::White),
-            };
-        }
-        
-        let board_strs: Vec<String> = board_1d.iter().map(|p| format!("{p}")).collect();
-        
-        write!(f, "\nX a b c d e f g h X\n")?;
-
-        write!(f, "1 {} 1\n", board_strs[0..8].join(" "))?;
-        write!(f, "2 {} 2\n", board_strs[8..16].join(" "))?;
-        write!(f, "3 {} 3\n", board_strs[16..24].join(" "))?;
-        write!(f, "4 {} 4\n", board_strs[24..32].join(" "))?;
-        write!(f, "5 {} 5\n", board_strs[32..40].join(" "))?;
-        write!(f, "6 {} 6\n", board_strs[40..48].join(" "))?;
-        write!(f, "7 {} 7\n", board_strs[48..56].join(" "))?;
-        write!(f, "8 {} 8\n", board_strs[56..64].join(" "))?;
-
-        write!(f, "X a b c d e f g h X\n")?;
-        Ok(())
+        write!(f, "{}", self.diagram(DiagramStyle::default()))
     }
 }
 
 impl Board {
     pub fn _blank() -> Self {
-        Self { pieces: vec![] }
+        Self {
+            by_kind: [Bitboard::EMPTY; 6],
+            by_colour: [Bitboard::EMPTY; 2],
+            piece_key: 0,
+            history: vec![],
+            side_to_move: Colour::White,
+            castling: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
     }
 
-    pub fn find_piece_by_pos(&mut self, x: i8, y: i8) -> Option<usize> {
-        let piece: &Piece = self
-            .pieces
-            .iter()
-            .filter(|p| p.pos[0] == x)
-            .filter(|p| p.pos[1] == y)
-            .nth(0)?;
+    /// Updates side to move, castling rights, en passant target and clocks for a piece
+    /// about to move from `from` to `to`.
+    pub fn record_move(
+        &mut self,
+        piece_type: PieceType,
+        from: Square,
+        to: Square,
+        capture: bool,
+    ) {
+        let colour = piece_type.get_colour();
+        let ranks = to.rank().index() - from.rank().index();
 
-        self.pieces.iter().position(|x| x == piece)
-    }
+        match piece_type {
+            PieceType::Pawn(_) if ranks.abs() == 2 => {
+                self.en_passant = from.offset(0, ranks / 2);
+            }
+            _ => self.en_passant = None,
+        }
 
-    pub fn get_tiles_between(a: [i8; 2], b: [i8; 2]) -> Option<Vec<[i8; 2]>> {
-        let xs: Vec<i8> = if a[0] > b[0] {
-            (b[0]+1..=a[0]).rev().collect()
-        } else {
-            (a[0]..b[0]).into_iter().collect()
+        if let PieceType::King(_) = piece_type {
+            match colour {
+                Colour::White => {
+                    self.castling.white_short = false;
+                    self.castling.white_long = false;
+                }
+                Colour::Black => {
+                    self.castling.black_short = false;
+                    self.castling.black_long = false;
+                }
+            }
+        }
+
+        // A rook leaving its corner, or being captured there, loses its castling right.
+        for pos in [from, to] {
+            match (pos.file(), pos.rank()) {
+                (File::A, Rank::FIRST) => self.castling.white_long = false,
+                (File::H, Rank::FIRST) => self.castling.white_short = false,
+                (File::A, Rank::EIGHTH) => self.castling.black_long = false,
+                (File::H, Rank::EIGHTH) => self.castling.black_short = false,
+                _ => (),
+            }
+        }
+
+        self.halfmove_clock = match (piece_type, capture) {
+            (PieceType::Pawn(_), _) | (_, true) => 0,
+            _ => self.halfmove_clock + 1,
         };
 
-        let ys: Vec<i8> = if a[1] > b[1] {
-            (b[1]+1..=a[1]).rev().collect()
+        if colour == Colour::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = colour.opposite();
+    }
+
+    pub fn piece_at(&self, pos: Square) -> Option<PieceType> {
+        let colour = if self.colour(Colour::White).contains(pos) {
+            Colour::White
+        } else if self.colour(Colour::Black).contains(pos) {
+            Colour::Black
         } else {
-            (a[1]..b[1]).into_iter().collect()
+            return None;
         };
 
-        if xs.len() == 0 {
-            Some(ys.iter().map(|i| [a[0], *i]).skip(1).collect())
-        } else if ys.len() == 0 {
-            Some(xs.iter().map(|i| [*i, a[1]]).skip(1).collect())
-        } else if xs.len() == ys.len() {
-            Some(xs.iter().zip(ys).map(|(x, y)| [*x, y]).skip(1).collect())
-        } else {
-            None
+        KINDS.iter()
+            .find(|kind| self.by_kind[**kind as usize].contains(pos))
+            .map(|kind| piece_type(*kind, colour))
+    }
+
+    /// Every piece on the board, from a1 to h8.
+    pub fn pieces(&self) -> impl Iterator<Item = Piece> + '_ {
+        self.occupied()
+            .squares()
+            .filter_map(|pos| Some(Piece::new(pos, self.piece_at(pos)?)))
+    }
+
+    /// The squares holding a piece of this type and colour.
+    pub fn bitboard(&self, piece_type: PieceType) -> Bitboard {
+        match kind(piece_type) {
+            Some(kind) => self.by_kind[kind as usize] & self.colour(piece_type.get_colour()),
+            None => Bitboard::EMPTY,
+        }
+    }
+
+    /// The squares holding a piece of `colour`.
+    pub fn colour(&self, colour: Colour) -> Bitboard {
+        self.by_colour[colour.index()]
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.by_colour[0] | self.by_colour[1]
+    }
+
+    /// Puts a piece on `pos`, replacing whatever stood there. An empty piece type clears the square.
+    pub fn put_piece(&mut self, pos: Square, piece_type: PieceType) {
+        self.remove_piece(pos);
+
+        if let Some(kind) = kind(piece_type) {
+            let bit = Bitboard::from_square(pos);
+            self.by_kind[kind as usize] |= bit;
+            self.by_colour[piece_type.get_colour().index()] |= bit;
+            self.piece_key ^= piece_key(pos, piece_type);
+        }
+    }
+
+    /// Takes the piece off `pos`, returning it.
+    pub fn remove_piece(&mut self, pos: Square) -> Option<PieceType> {
+        let piece_type = self.piece_at(pos)?;
+        let mask = !Bitboard::from_square(pos);
+
+        for bitboard in self.by_kind.iter_mut().chain(self.by_colour.iter_mut()) {
+            *bitboard &= mask;
+        }
+        self.piece_key ^= piece_key(pos, piece_type);
+
+        Some(piece_type)
+    }
+
+    /// Moves the piece on `from` to `to`, removing anything standing on `to`.
+    pub fn move_piece(&mut self, from: Square, to: Square) {
+        if let Some(piece_type) = self.remove_piece(from) {
+            self.put_piece(to, piece_type);
         }
     }
 
-    pub fn get_intervening_pieces(&mut self, between: &Vec<[i8; 2]>) -> Vec<[i8; 2]> {
+    /// The squares strictly between `a` and `b`, starting next to `a`.
+    /// `None` when the two squares share no rank, file or diagonal.
+    pub fn get_tiles_between(a: Square, b: Square) -> Option<Vec<Square>> {
+        let files = b.file().index() - a.file().index();
+        let ranks = b.rank().index() - a.rank().index();
+
+        if files != 0 && ranks != 0 && files.abs() != ranks.abs() {
+            return None;
+        }
+
+        let steps = files.abs().max(ranks.abs());
+
+        (1..steps)
+            .map(|i| a.offset(files.signum() * i, ranks.signum() * i))
+            .collect()
+    }
+
+    pub fn get_intervening_pieces(&self, between: &[Square]) -> Vec<Square> {
         between.iter()
-            .filter(|t| self.find_piece_by_pos(t[0], t[1]).is_some())
-            .map(|t| *t)
+            .filter(|t| self.piece_at(**t).is_some())
+            .copied()
             .collect()
     }
+}
 
-    pub fn check_for_collisions(
-        &mut self,
-        from: [i8; 2],
-        to: [i8; 2],
-        piece_type: PieceType,
-    ) -> bool {
-        match piece_type {
-            PieceType::Pawn(_) | PieceType::King(_) | PieceType::Knight(_) => false,
-            PieceType::Queen(_) | PieceType::Bishop(_) | PieceType::Rook(_) => {
-                let between = Self::get_tiles_between(from, to).unwrap();
+/// The kinds of piece, in the order of `Board::by_kind`.
+#[derive(Clone, Copy)]
+enum Kind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
 
-                let full_squares: Vec<[i8; 2]> = self.get_intervening_pieces(&between);
+const KINDS: [Kind; 6] = [
+    Kind::Pawn,
+    Kind::Knight,
+    Kind::Bishop,
+    Kind::Rook,
+    Kind::Queen,
+    Kind::King,
+];
+
+fn kind(piece_type: PieceType) -> Option<Kind> {
+    match piece_type {
+        PieceType::Pawn(_) => Some(Kind::Pawn),
+        PieceType::Knight(_) => Some(Kind::Knight),
+        PieceType::Bishop(_) => Some(Kind::Bishop),
+        PieceType::Rook(_) => Some(Kind::Rook),
+        PieceType::Queen(_) => Some(Kind::Queen),
+        PieceType::King(_) => Some(Kind::King),
+        PieceType::Empty(_) => None,
+    }
+}
 
-                (between.len() > 0) && (full_squares.len() > 0)
-            }
-            PieceType::Empty(_) => false,
-        }
+fn piece_type(kind: Kind, colour: Colour) -> PieceType {
+    match kind {
+        Kind::Pawn => PieceType::Pawn(colour),
+        Kind::Knight => PieceType::Knight(colour),
+        Kind::Bishop => PieceType::Bishop(colour),
+        Kind::Rook => PieceType::Rook(colour),
+        Kind::Queen => PieceType::Queen(colour),
+        Kind::King => PieceType::King(colour),
     }
 }
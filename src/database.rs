@@ -0,0 +1,234 @@
+use std::{fs::read_to_string, path::Path};
+
+use crate::{
+    error::{Location, SjakkError},
+    pgn::{GameResult, PgnGame, TagPair},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_games() {
+        let database = PgnDatabase::open("test_database.pgn").unwrap();
+        let entries: Vec<GameEntry> = database.entries().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].number, 1);
+        assert_eq!(entries[2].tag("White"), Some("Fischer, Robert James"));
+        assert_eq!(entries[1].game().unwrap().turns.len(), 4);
+    }
+
+    #[test]
+    fn test_games_without_tags() {
+        let database = PgnDatabase::new("1. e4 e5 1-0\n1. d4 {a [bracket] in a comment} d5 2. c4 0-1 1. c4 *");
+        let games: Vec<PgnGame> = database.games().collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[1].turns.len(), 2);
+        assert_eq!(games[1].result, GameResult::BlackWins);
+    }
+
+    #[test]
+    fn test_search() {
+        let database = PgnDatabase::open("test_database.pgn").unwrap();
+        let fischer: Vec<usize> = database
+            .search("White", "Fischer")
+            .map(|e| e.unwrap().number)
+            .collect();
+
+        assert_eq!(fischer, vec![1, 3]);
+        assert_eq!(database.search("Black", "Nobody").count(), 0);
+    }
+
+    #[test]
+    fn test_error_location() {
+        let database = PgnDatabase::new("1. e4 e5 1-0\n\n1. d4 d5\n2. c4 Qx9 0-1");
+        let error = database.games().nth(1).unwrap().unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "line 4, column 7: move without a destination square 'Qx9'"
+        );
+    }
+}
+
+/// A collection of games read from a single PGN file.
+///
+/// The source is kept as text and split into games on demand, so only the games
+/// that are actually asked for get their movetext parsed.
+#[derive(Debug, Clone)]
+pub struct PgnDatabase {
+    source: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameEntry<'a> {
+    pub number: usize,
+    pub tags: Vec<TagPair>,
+    text: &'a str,
+    source: &'a str,
+}
+
+impl<'a> GameEntry<'a> {
+    pub fn tag<S>(&self, name: S) -> Option<&str>
+    where
+        S: AsRef<str>,
+    {
+        self.tags
+            .iter()
+            .find(|t| t.name == name.as_ref())
+            .map(|t| t.value.as_str())
+    }
+
+    pub fn game(&self) -> Result<PgnGame, SjakkError> {
+        PgnGame::parse(self.text).map_err(|e| self.in_source(e))
+    }
+
+    /// Moves the location of a parse error from the game text to the whole file.
+    fn in_source(&self, error: SjakkError) -> SjakkError {
+        match error {
+            SjakkError::Parse {
+                token,
+                reason,
+                location: Some(location),
+            } => {
+                let start = Location::in_text(
+                    self.source,
+                    self.text.as_ptr() as usize - self.source.as_ptr() as usize,
+                );
+                let column = match location.line {
+                    1 => start.column + location.column - 1,
+                    _ => location.column,
+                };
+
+                SjakkError::parse(token, reason).at(Location {
+                    line: start.line + location.line - 1,
+                    column,
+                })
+            }
+            error => error,
+        }
+    }
+}
+
+impl PgnDatabase {
+    pub fn new<S>(source: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            source: source.into(),
+        }
+    }
+
+    pub fn open<P>(path: P) -> Result<Self, SjakkError>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::new(read_to_string(path)?))
+    }
+
+    /// Every game in the file together with its tag pairs, numbered from 1.
+    pub fn entries(&self) -> impl Iterator<Item = Result<GameEntry<'_>, SjakkError>> {
+        GameSplitter { text: &self.source }
+            .enumerate()
+            .map(|(i, text)| {
+                let mut entry = GameEntry {
+                    number: i + 1,
+                    tags: vec![],
+                    text,
+                    source: &self.source,
+                };
+                entry.tags = PgnGame::parse_tags(text).map_err(|e| entry.in_source(e))?;
+                Ok(entry)
+            })
+    }
+
+    pub fn games(&self) -> impl Iterator<Item = Result<PgnGame, SjakkError>> + '_ {
+        self.entries().map(|entry| entry?.game())
+    }
+
+    /// Entries whose tag `name` contains `value`, e.g. `search("White", "Fischer")`.
+    pub fn search<'a>(
+        &'a self,
+        name: &'a str,
+        value: &'a str,
+    ) -> impl Iterator<Item = Result<GameEntry<'a>, SjakkError>> {
+        self.entries().filter(move |e| match e {
+            Ok(entry) => entry.tag(name).is_some_and(|t| t.contains(value)),
+            Err(_) => true,
+        })
+    }
+}
+
+/// Splits PGN text into the text of the individual games.
+///
+/// A game ends at its result token, or where the tag pairs of the next game begin.
+struct GameSplitter<'a> {
+    text: &'a str,
+}
+
+impl<'a> Iterator for GameSplitter<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = self.text.trim_start();
+        if text.is_empty() {
+            return None;
+        }
+
+        let mut chars = text.char_indices().peekable();
+        let mut end = text.len();
+        let mut depth: usize = 0;
+        let mut seen_movetext = false;
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '{' => {
+                    chars.by_ref().find(|(_, c)| *c == '}');
+                }
+                ';' => {
+                    chars.by_ref().find(|(_, c)| *c == '\n');
+                }
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                '[' if seen_movetext && depth == 0 => {
+                    end = i;
+                    break;
+                }
+                '[' => {
+                    let mut quoted = false;
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '\\' if quoted => {
+                                chars.next();
+                            }
+                            '"' => quoted = !quoted,
+                            ']' if !quoted => break,
+                            _ => (),
+                        }
+                    }
+                }
+                c if c.is_whitespace() => (),
+                _ => {
+                    seen_movetext = true;
+                    let mut token_end = i + c.len_utf8();
+                    while let Some((j, c)) = chars.next_if(|(_, c)| !PgnGame::is_delimiter(*c)) {
+                        token_end = j + c.len_utf8();
+                    }
+
+                    if depth == 0 && GameResult::from_token(&text[i..token_end]).is_some() {
+                        end = token_end;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let (game, rest) = text.split_at(end);
+        self.text = rest;
+        Some(game)
+    }
+}
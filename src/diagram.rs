@@ -0,0 +1,158 @@
+use std::fmt::Display;
+
+use crate::{
+    board::Board,
+    fen::fen_symbol,
+    square::{File, Rank, Square},
+    types::{Colour, PieceType},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(diagram: Diagram) -> Vec<String> {
+        diagram.to_string().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_orientation() {
+        let board = Board::default();
+        let lines = draw(board.diagram(DiagramStyle {
+            ascii: true,
+            ..DiagramStyle::default()
+        }));
+
+        assert_eq!(lines[0], "");
+        assert_eq!(lines[1], "  a b c d e f g h");
+        assert_eq!(lines[2], "8 r n b q k b n r 8");
+        assert_eq!(lines[4], "6 . . . . . . . . 6");
+        assert_eq!(lines[9], "1 R N B Q K B N R 1");
+        assert_eq!(lines[10], "  a b c d e f g h");
+
+        let lines = draw(board.diagram(DiagramStyle {
+            flipped: true,
+            ascii: true,
+            ..DiagramStyle::default()
+        }));
+
+        assert_eq!(lines[1], "  h g f e d c b a");
+        assert_eq!(lines[2], "1 R N B K Q B N R 1");
+        assert_eq!(lines[9], "8 r n b k q b n r 8");
+    }
+
+    #[test]
+    fn test_styles() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let lines = draw(board.diagram(DiagramStyle {
+            coordinates: false,
+            ascii: true,
+            ..DiagramStyle::default()
+        }));
+
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[1], ". . . . k . . .");
+        assert_eq!(lines[8], ". . . . K . . .");
+
+        let lines = draw(board.diagram(DiagramStyle::default()));
+        assert_eq!(lines[2], "8         ♚       8");
+        assert_eq!(
+            board.to_string(),
+            board.diagram(DiagramStyle::default()).to_string()
+        );
+    }
+}
+
+/// How a board is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagramStyle {
+    /// Draw the board from Black's side, with rank 1 at the top and the h-file on the left.
+    pub flipped: bool,
+    /// Label the files and ranks around the board.
+    pub coordinates: bool,
+    /// Use the FEN letters, `PNBRQK` and `pnbrqk`, and `.` for empty squares instead of
+    /// the Unicode chess figurines.
+    pub ascii: bool,
+}
+
+impl Default for DiagramStyle {
+    fn default() -> Self {
+        Self {
+            flipped: false,
+            coordinates: true,
+            ascii: false,
+        }
+    }
+}
+
+/// A board drawn in a given style, from `Board::diagram`.
+#[derive(Debug, Clone, Copy)]
+pub struct Diagram<'a> {
+    board: &'a Board,
+    style: DiagramStyle,
+}
+
+impl Board {
+    pub fn diagram(&self, style: DiagramStyle) -> Diagram<'_> {
+        Diagram { board: self, style }
+    }
+}
+
+impl Diagram<'_> {
+    /// The files from left to right and the ranks from top to bottom.
+    fn order(&self) -> (Vec<File>, Vec<Rank>) {
+        let files: Vec<File> = (0..8).filter_map(File::new).collect();
+        let ranks: Vec<Rank> = (0..8).rev().filter_map(Rank::new).collect();
+
+        if self.style.flipped {
+            (
+                files.into_iter().rev().collect(),
+                ranks.into_iter().rev().collect(),
+            )
+        } else {
+            (files, ranks)
+        }
+    }
+
+    fn symbol(&self, square: Square) -> String {
+        match (self.board.piece_at(square), self.style.ascii) {
+            (Some(piece_type), true) => fen_symbol(piece_type).to_string(),
+            (Some(piece_type), false) => piece_type.to_string(),
+            (None, true) => ".".to_string(),
+            (None, false) => PieceType::Empty(Colour::White).to_string(),
+        }
+    }
+}
+
+impl Display for Diagram<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (files, ranks) = self.order();
+        let file_labels: Vec<String> = files.iter().map(File::to_string).collect();
+        let file_labels = format!("  {}", file_labels.join(" "));
+
+        writeln!(f)?;
+        if self.style.coordinates {
+            writeln!(f, "{file_labels}")?;
+        }
+
+        for rank in &ranks {
+            let squares: Vec<String> = files
+                .iter()
+                .map(|file| self.symbol(Square::new(*file, *rank)))
+                .collect();
+            let squares = squares.join(" ");
+
+            if self.style.coordinates {
+                writeln!(f, "{rank} {squares} {rank}")?;
+            } else {
+                writeln!(f, "{squares}")?;
+            }
+        }
+
+        if self.style.coordinates {
+            writeln!(f, "{file_labels}")?;
+        }
+
+        Ok(())
+    }
+}
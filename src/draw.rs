@@ -0,0 +1,206 @@
+use crate::{
+    bitboard::Bitboard,
+    board::Board,
+    types::{Colour, DrawRule, PieceType},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Outcome;
+
+    fn play(board: &mut Board, moves: &str) {
+        for uci in moves.split_whitespace() {
+            let mv = board.parse_uci(uci).unwrap();
+            board.make_move(&mv);
+        }
+    }
+
+    #[test]
+    fn test_repetition() {
+        let shuffle = "g1f3 g8f6 f3g1 f6g8";
+        let mut board = Board::default();
+
+        play(&mut board, shuffle);
+        assert_eq!(board.repetitions(), 2);
+        assert_eq!(board.draw_rule(), None);
+
+        play(&mut board, shuffle);
+        assert_eq!(board.repetitions(), 3);
+        assert_eq!(board.draw_rule(), Some(DrawRule::ThreefoldRepetition));
+        assert_eq!(board.outcome(), None);
+
+        let undo = board.make_move(&board.parse_uci("g1f3").unwrap());
+        assert_eq!(board.repetitions(), 3);
+        board.unmake_move(undo);
+        assert_eq!(board.repetitions(), 3);
+
+        play(&mut board, &format!("{shuffle} {shuffle}"));
+        assert_eq!(
+            board.outcome(),
+            Some(Outcome::Draw(DrawRule::FivefoldRepetition))
+        );
+    }
+
+    #[test]
+    fn test_irreversible_moves() {
+        // The pawn move resets the clock, so earlier positions can't come back.
+        let mut board = Board::default();
+        play(
+            &mut board,
+            "g1f3 g8f6 f3g1 f6g8 e2e4 e7e5 g1f3 g8f6 f3g1 f6g8",
+        );
+
+        assert_eq!(board.halfmove_clock, 4);
+        assert_eq!(board.repetitions(), 2);
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        let dead = |fen: &str| Board::from_fen(fen).unwrap().is_insufficient_material();
+
+        assert!(dead("8/8/4k3/8/8/3K4/8/8 w - - 0 1"));
+        assert!(dead("8/8/4k3/8/8/3K4/8/5B2 w - - 0 1"));
+        assert!(dead("8/8/4k3/8/8/3K4/8/6N1 b - - 0 1"));
+        // Bishops on c1, f4 and g7 all stand on dark squares.
+        assert!(dead("8/6b1/4k3/8/5B2/3K4/8/2B5 w - - 0 1"));
+
+        assert!(!Board::default().is_insufficient_material());
+        assert!(!dead("8/8/4k3/8/8/3K4/8/2B2B2 w - - 0 1"));
+        assert!(!dead("8/8/4k3/8/8/3K4/8/5BN1 w - - 0 1"));
+        assert!(!dead("8/8/4k3/8/8/3K4/8/1N4N1 w - - 0 1"));
+        assert!(!dead("8/8/4k3/8/8/3K4/4P3/8 w - - 0 1"));
+        assert!(!dead("8/8/4kn2/8/8/3K4/8/6N1 w - - 0 1"));
+
+        let board = Board::from_fen("8/8/4k3/8/8/3K4/8/5B2 b - - 0 1").unwrap();
+        assert_eq!(
+            board.outcome(),
+            Some(Outcome::Draw(DrawRule::InsufficientMaterial))
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        // White's knight can still mate, as black's rook can block its own king.
+        let board = Board::from_fen("8/8/4k3/4r3/8/3K4/8/6N1 w - - 0 1").unwrap();
+
+        assert!(!board.is_timeout_draw(Colour::Black));
+        assert!(!board.is_timeout_draw(Colour::White));
+
+        let board = Board::from_fen("8/8/4k3/4r3/8/3K4/8/8 w - - 0 1").unwrap();
+        assert!(board.is_timeout_draw(Colour::Black));
+        assert!(!board.is_timeout_draw(Colour::White));
+    }
+
+    #[test]
+    fn test_move_rules() {
+        let mut board = Board::from_fen("7k/8/8/8/8/8/R7/K7 w - - 99 80").unwrap();
+        assert_eq!(board.draw_rule(), None);
+
+        play(&mut board, "a2b2");
+        assert_eq!(board.draw_rule(), Some(DrawRule::FiftyMoves));
+        assert_eq!(board.outcome(), None);
+
+        let mut board = Board::from_fen("7k/8/8/8/8/8/R7/K7 w - - 149 80").unwrap();
+        play(&mut board, "a2b2");
+        assert_eq!(
+            board.outcome(),
+            Some(Outcome::Draw(DrawRule::SeventyFiveMoves))
+        );
+
+        // Mate on the move that reaches the limit still wins.
+        let mut board = Board::from_fen("7k/8/6K1/8/8/8/8/R7 w - - 149 80").unwrap();
+        play(&mut board, "a1a8");
+        assert_eq!(board.draw_rule(), None);
+        assert!(board.is_checkmate());
+    }
+}
+
+impl Board {
+    /// How many times the current position has occurred, counting this time.
+    ///
+    /// Only positions since the last capture or pawn move are looked at, as none before
+    /// it can come back.
+    pub fn repetitions(&self) -> usize {
+        let key = self.zobrist();
+
+        1 + self
+            .history
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .filter(|k| **k == key)
+            .count()
+    }
+
+    /// The strongest draw rule that applies to the position, if any.
+    ///
+    /// The rules that end the game by themselves take precedence over the claimable
+    /// threefold repetition and fifty-move rule. None apply after a mate.
+    pub fn draw_rule(&self) -> Option<DrawRule> {
+        let repetitions = self.repetitions();
+
+        let rule = if self.is_insufficient_material() {
+            DrawRule::InsufficientMaterial
+        } else if repetitions >= 5 {
+            DrawRule::FivefoldRepetition
+        } else if self.halfmove_clock >= 150 {
+            DrawRule::SeventyFiveMoves
+        } else if repetitions >= 3 {
+            DrawRule::ThreefoldRepetition
+        } else if self.halfmove_clock >= 100 {
+            DrawRule::FiftyMoves
+        } else {
+            return None;
+        };
+
+        (!self.is_checkmate()).then_some(rule)
+    }
+
+    /// Whether neither side can ever mate: K vs K, K+B vs K, K+N vs K, or only bishops
+    /// left that all stand on squares of one colour.
+    pub fn is_insufficient_material(&self) -> bool {
+        self.has_insufficient_material(Colour::White)
+            && self.has_insufficient_material(Colour::Black)
+    }
+
+    /// Whether `colour` can't mate by any series of legal moves, however badly the
+    /// other side plays.
+    ///
+    /// A lone king never mates. A single knight, or bishops all on one colour of square,
+    /// can only mate when the other side has pieces to block its own king with.
+    pub fn has_insufficient_material(&self, colour: Colour) -> bool {
+        let pieces = |piece_type| self.bitboard(piece_type);
+        let heavy = |colour| {
+            pieces(PieceType::Pawn(colour))
+                | pieces(PieceType::Rook(colour))
+                | pieces(PieceType::Queen(colour))
+        };
+        let knights = pieces(PieceType::Knight(colour));
+        let bishops = pieces(PieceType::Bishop(colour));
+        let others = self.colour(colour.opposite()) & !pieces(PieceType::King(colour.opposite()));
+
+        if !heavy(colour).is_empty() {
+            return false;
+        }
+
+        match (knights.count(), bishops.count()) {
+            (0, 0) => true,
+            (1, 0) => others.is_empty(),
+            (0, _) => {
+                let all_bishops = bishops | pieces(PieceType::Bishop(colour.opposite()));
+                let on_one_colour = (all_bishops & Bitboard::DARK_SQUARES).is_empty()
+                    || (all_bishops & !Bitboard::DARK_SQUARES).is_empty();
+
+                on_one_colour && (others & !all_bishops).is_empty()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether running out of time draws for `flagged` rather than losing, because the
+    /// other side couldn't have mated anyway.
+    pub fn is_timeout_draw(&self, flagged: Colour) -> bool {
+        self.has_insufficient_material(flagged.opposite())
+    }
+}
@@ -0,0 +1,143 @@
+use std::{convert::Infallible, fmt::Display, io};
+
+use crate::square::Square;
+
+/// Where in a text something went wrong, both counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// The location of the byte `offset` in `text`.
+    pub fn in_text(text: &str, offset: usize) -> Self {
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SjakkError {
+    /// A token in a game, move or FEN that could not be understood.
+    Parse {
+        token: String,
+        reason: String,
+        location: Option<Location>,
+    },
+    /// More than one piece can make the move, `candidates` are the squares they stand on.
+    AmbiguousMove {
+        notation: String,
+        candidates: Vec<Square>,
+        ply: Option<usize>,
+    },
+    IllegalMove {
+        notation: String,
+        reason: String,
+        ply: Option<usize>,
+    },
+    /// No piece of the moving kind can reach the destination.
+    NoSuchPiece {
+        notation: String,
+        ply: Option<usize>,
+    },
+    /// No game in the file matches the game number and player names asked for.
+    NoSuchGame,
+    Io(io::Error),
+}
+
+impl SjakkError {
+    pub fn parse<T, R>(token: T, reason: R) -> Self
+    where
+        T: Into<String>,
+        R: Into<String>,
+    {
+        Self::Parse {
+            token: token.into(),
+            reason: reason.into(),
+            location: None,
+        }
+    }
+
+    /// Places a parse error at `location`, unless it already has one.
+    pub fn at(mut self, at: Location) -> Self {
+        if let Self::Parse { location, .. } = &mut self {
+            location.get_or_insert(at);
+        }
+        self
+    }
+
+    /// Marks a move error with the ply (half-move, counted from 1) it happened in.
+    pub fn at_ply(mut self, at: usize) -> Self {
+        if let Self::AmbiguousMove { ply, .. }
+        | Self::IllegalMove { ply, .. }
+        | Self::NoSuchPiece { ply, .. } = &mut self
+        {
+            ply.get_or_insert(at);
+        }
+        self
+    }
+}
+
+impl Display for SjakkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse {
+                location: Some(location),
+                ..
+            } => write!(f, "line {}, column {}: ", location.line, location.column)?,
+            Self::AmbiguousMove { ply: Some(ply), .. }
+            | Self::IllegalMove { ply: Some(ply), .. }
+            | Self::NoSuchPiece { ply: Some(ply), .. } => write!(f, "ply {ply}: ")?,
+            _ => (),
+        }
+
+        match self {
+            Self::Parse { token, reason, .. } => write!(f, "{reason} '{token}'"),
+            Self::AmbiguousMove {
+                notation,
+                candidates,
+                ..
+            } => {
+                let squares: Vec<String> = candidates.iter().map(|c| c.to_string()).collect();
+                write!(
+                    f,
+                    "{notation} is ambiguous, it can be played from {}",
+                    squares.join(", ")
+                )
+            }
+            Self::IllegalMove {
+                notation, reason, ..
+            } => write!(f, "{notation} is illegal, {reason}"),
+            Self::NoSuchPiece { notation, .. } => write!(f, "no piece can play {notation}"),
+            Self::NoSuchGame => write!(f, "no game in the file matches the selection"),
+            Self::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for SjakkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SjakkError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<Infallible> for SjakkError {
+    fn from(error: Infallible) -> Self {
+        match error {}
+    }
+}
@@ -0,0 +1,329 @@
+use crate::{
+    board::Board,
+    error::{Location, SjakkError},
+    piece::Piece,
+    square::{Rank, Square},
+    types::{CastlingRights, Colour, PieceType},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_position() {
+        let board = Board::from_fen(START_POSITION).unwrap();
+
+        assert_eq!(board, Board::default());
+        assert_eq!(board.side_to_move, Colour::White);
+        assert_eq!(board.castling, CastlingRights::all());
+        assert_eq!(board.en_passant, None);
+        assert_eq!(board.halfmove_clock, 0);
+        assert_eq!(board.fullmove_number, 1);
+    }
+
+    #[test]
+    fn test_position_fields() {
+        let board =
+            Board::from_fen("rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R w Kq c6 0 3").unwrap();
+
+        assert_eq!(board.pieces().count(), 32);
+        assert!(board.piece_at("f3".parse().unwrap()).is_some());
+        assert!(board.piece_at("g1".parse().unwrap()).is_none());
+        assert_eq!(board.side_to_move, Colour::White);
+        assert!(board.castling.white_short && !board.castling.white_long);
+        assert!(!board.castling.black_short && board.castling.black_long);
+        assert_eq!(board.en_passant, Some("c6".parse().unwrap()));
+        assert_eq!(board.halfmove_clock, 0);
+        assert_eq!(board.fullmove_number, 3);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for fen in [
+            START_POSITION,
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R w Kq c6 0 3",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/8/8/8/8/8/8/K6k b - - 99 120",
+        ] {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_missing_clocks() {
+        let board = Board::from_fen("8/8/8/8/8/8/8/K6k w - -").unwrap();
+
+        assert_eq!(board.pieces().count(), 2);
+        assert_eq!(board.halfmove_clock, 0);
+        assert_eq!(board.fullmove_number, 1);
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(Board::from_fen("").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8 w - - 0 1").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K7k w - - 0 1").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K5xk w - - 0 1").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K6k x - - 0 1").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K6k w KX - 0 1").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K6k w - e4 0 1").is_err());
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - e3 0 1").is_err());
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/4K3 b - e6 0 1").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K6k w - - -1 1").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K6k w - - 0 0").is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/K6k w - - 0 1 extra").is_err());
+        assert!(Board::from_fen("8888888888888888/8/8/8/8/8/8/8 w - - 0 1").is_err());
+    }
+
+    #[test]
+    fn test_error_location() {
+        let error = Board::from_fen("8/8/8/8/8/8/8/K5xk w - - 0 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 17: invalid piece on rank 1 'x'"
+        );
+
+        let error = Board::from_fen("8/8/8/8/8/8/8/K6k w KX - 0 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 21: invalid castling rights 'KX'"
+        );
+
+        let error = Board::from_fen("8/8/8/8/8/8/8/K5k w - - 0 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 15: rank 1 with 7 files instead of 8 'K5k'"
+        );
+    }
+}
+
+pub const START_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// An error for `token`, which must be a slice of `fen`, pointing at where it starts.
+fn invalid(fen: &str, token: &str, reason: &str) -> SjakkError {
+    let offset = (token.as_ptr() as usize)
+        .checked_sub(fen.as_ptr() as usize)
+        .filter(|offset| *offset <= fen.len())
+        .unwrap_or(0);
+
+    SjakkError::parse(token, reason).at(Location::in_text(fen, offset))
+}
+
+impl Board {
+    /// Builds a board from Forsyth-Edwards Notation.
+    ///
+    /// The halfmove clock and fullmove number may be left out, they then default to 0 and 1.
+    pub fn from_fen<S>(fen: S) -> Result<Self, SjakkError>
+    where
+        S: AsRef<str>,
+    {
+        let fen = fen.as_ref();
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+
+        if !(4..=6).contains(&fields.len()) {
+            return Err(invalid(
+                fen,
+                fen,
+                &format!("FEN with {} fields instead of 4 to 6", fields.len()),
+            ));
+        }
+
+        let mut board = Self::_blank();
+
+        for piece in parse_placement(fen, fields[0])? {
+            board.put_piece(piece.pos, piece.piece_type);
+        }
+        board.side_to_move = match fields[1] {
+            "w" => Colour::White,
+            "b" => Colour::Black,
+            s => return Err(invalid(fen, s, "invalid side to move")),
+        };
+        board.castling = parse_castling(fen, fields[2])?;
+        // The target square is behind the pawn that just moved, so it's on the third rank
+        // when Black is to move and on the sixth when White is.
+        let en_passant_rank = match board.side_to_move {
+            Colour::White => Rank::SIXTH,
+            Colour::Black => Rank::THIRD,
+        };
+        board.en_passant = match fields[3] {
+            "-" => None,
+            s => Some(
+                s.parse::<Square>()
+                    .ok()
+                    .filter(|pos| pos.rank() == en_passant_rank)
+                    .ok_or_else(|| invalid(fen, s, "invalid en passant square"))?,
+            ),
+        };
+
+        if let Some(s) = fields.get(4) {
+            board.halfmove_clock = s
+                .parse()
+                .map_err(|_| invalid(fen, s, "invalid halfmove clock"))?;
+        }
+
+        if let Some(s) = fields.get(5) {
+            board.fullmove_number = s
+                .parse()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| invalid(fen, s, "invalid fullmove number"))?;
+        }
+
+        Ok(board)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut placement: Vec<String> = vec![];
+
+        for y in (0..8).rev() {
+            let mut rank = String::new();
+            let mut empty = 0;
+
+            for x in 0..8 {
+                match Square::from_coords(x, y).and_then(|pos| self.piece_at(pos)) {
+                    Some(piece_type) => {
+                        if empty > 0 {
+                            rank.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        rank.push(fen_symbol(piece_type));
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+            }
+            placement.push(rank);
+        }
+
+        let side_to_move = match self.side_to_move {
+            Colour::White => "w",
+            Colour::Black => "b",
+        };
+
+        let mut castling = String::new();
+        for (allowed, symbol) in [
+            (self.castling.white_short, 'K'),
+            (self.castling.white_long, 'Q'),
+            (self.castling.black_short, 'k'),
+            (self.castling.black_long, 'q'),
+        ] {
+            if allowed {
+                castling.push(symbol);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant {
+            Some(pos) => pos.to_string(),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {side_to_move} {castling} {en_passant} {} {}",
+            placement.join("/"),
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+pub(crate) fn fen_symbol(piece_type: PieceType) -> char {
+    let symbol = match piece_type {
+        PieceType::Pawn(_) => 'P',
+        PieceType::King(_) => 'K',
+        PieceType::Queen(_) => 'Q',
+        PieceType::Bishop(_) => 'B',
+        PieceType::Knight(_) => 'N',
+        PieceType::Rook(_) => 'R',
+        PieceType::Empty(_) => '1',
+    };
+
+    match piece_type.get_colour() {
+        Colour::White => symbol,
+        Colour::Black => symbol.to_ascii_lowercase(),
+    }
+}
+
+fn parse_placement(fen: &str, value: &str) -> Result<Vec<Piece>, SjakkError> {
+    let ranks: Vec<&str> = value.split('/').collect();
+
+    if ranks.len() != 8 {
+        return Err(invalid(
+            fen,
+            value,
+            &format!("piece placement with {} ranks instead of 8", ranks.len()),
+        ));
+    }
+
+    let mut pieces = vec![];
+
+    for (i, rank) in ranks.iter().enumerate() {
+        let y = 7 - i as i8;
+        let mut x: usize = 0;
+
+        for (j, symbol) in rank.char_indices() {
+            if let Some(empty) = symbol.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                x += empty as usize;
+                continue;
+            }
+
+            if !"PNBRQKpnbrqk".contains(symbol) {
+                return Err(invalid(
+                    fen,
+                    &rank[j..j + symbol.len_utf8()],
+                    &format!("invalid piece on rank {}", y + 1),
+                ));
+            }
+
+            let colour = if symbol.is_uppercase() {
+                Colour::White
+            } else {
+                Colour::Black
+            };
+
+            if let Some(pos) = i8::try_from(x).ok().and_then(|x| Square::from_coords(x, y)) {
+                pieces.push(Piece::new(
+                    pos,
+                    PieceType::from_char_and_colour(symbol.to_ascii_uppercase(), colour),
+                ));
+            }
+            x += 1;
+        }
+
+        if x != 8 {
+            return Err(invalid(
+                fen,
+                rank,
+                &format!("rank {} with {x} files instead of 8", y + 1),
+            ));
+        }
+    }
+
+    Ok(pieces)
+}
+
+fn parse_castling(fen: &str, value: &str) -> Result<CastlingRights, SjakkError> {
+    let mut castling = CastlingRights::default();
+
+    if value == "-" {
+        return Ok(castling);
+    }
+
+    for symbol in value.chars() {
+        match symbol {
+            'K' => castling.white_short = true,
+            'Q' => castling.white_long = true,
+            'k' => castling.black_short = true,
+            'q' => castling.black_long = true,
+            _ => return Err(invalid(fen, value, "invalid castling rights")),
+        }
+    }
+
+    Ok(castling)
+}
@@ -0,0 +1,21 @@
+#![allow(clippy::items_after_test_module)]
+
+pub mod bitboard;
+pub mod board;
+pub mod database;
+pub mod diagram;
+pub mod draw;
+pub mod error;
+pub mod fen;
+pub mod movegen;
+pub mod perft;
+pub mod pgn;
+pub mod pgn_writer;
+pub mod piece;
+pub mod replay;
+pub mod san;
+pub mod square;
+pub mod turn;
+pub mod types;
+pub mod uci;
+pub mod zobrist;
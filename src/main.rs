@@ -1,52 +1,348 @@
-mod board;
-mod piece;
-mod turn;
-mod types;
-
-
+use std::fmt::Display;
 use std::fs::read_to_string;
-use std::io::Error;
+use std::io::{stdin, stdout, BufRead, Write};
 use std::env::args;
-
-use types::PieceType;
-
-use crate::board::Board;
-use crate::piece::Piece;
-use crate::turn::{Move, Turn};
-use crate::types::Capture;
+use std::process::ExitCode;
+
+use sjakk::board::Board;
+use sjakk::database::{GameEntry, PgnDatabase};
+use sjakk::diagram::DiagramStyle;
+use sjakk::error::{Location, SjakkError};
+use sjakk::pgn::{GameResult, PgnGame};
+use sjakk::pgn_writer::PgnWriter;
+use sjakk::replay::Replayer;
+use sjakk::turn::{Move, Turn};
+use sjakk::types::{self, Check, Colour, DrawRule};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::*;
+    use sjakk::types::*;
 
     #[test]
     fn test_read_game() {
-        let r = read_game("test_game.txt").unwrap();
+        let r = read_game("test_game.txt", &Options::default())
+            .unwrap()
+            .turns;
         let mock_turn_1 = Turn {
             value: [
-                Move {
+                Some(Move {
                     piece: PieceType::Pawn(Colour::White),
                     castle: Castle::No,
                     capture: Capture::No,
                     promotion: Promotion::No,
                     check: Check::No,
-                    from: None,
-                    to: Some([4, 3]),
-                },
-                Move {
+                    from: (None, None),
+                    to: Some("e4".parse().unwrap()),
+                }),
+                Some(Move {
                     piece: PieceType::Pawn(Colour::Black),
                     castle: Castle::No,
                     capture: Capture::No,
                     promotion: Promotion::No,
                     check: Check::No,
-                    from: None,
-                    to: Some([3, 5]),
-                },
+                    from: (None, None),
+                    to: Some("d6".parse().unwrap()),
+                }),
             ]
         };
 
-        assert_eq!(r.iter().nth(0).unwrap(), &mock_turn_1);
+        assert_eq!(r.first().unwrap(), &mock_turn_1);
+    }
+
+    #[test]
+    fn test_read_pgn() {
+        let r = read_game("bobby_game.txt", &Options::default())
+            .unwrap()
+            .turns;
+        let last_turn = r.last().unwrap();
+
+        assert_eq!(r.len(), 56);
+        assert!(last_turn.value[1].is_none());
+
+        let final_board = r
+            .iter()
+            .flat_map(Turn::plies)
+            .try_fold(Board::default(), make_a_ply)
+            .unwrap();
+        assert_eq!(final_board.pieces().count(), 12);
+    }
+
+    #[test]
+    fn test_select_game() {
+        let args = ["test_database.pgn", "--white", "Fischer", "--game", "3"];
+        let options = Options::from_args(args.iter().map(|a| a.to_string())).unwrap();
+        let r = read_game(&options.game_file, &options).unwrap().turns;
+
+        assert_eq!(r.len(), 4);
+        assert_eq!(r[0], Turn::new_from_notation("Nf3 Nf6").unwrap());
+
+        let args = ["test_database.pgn", "--game", "2"];
+        let options = Options::from_args(args.iter().map(|a| a.to_string())).unwrap();
+        let r = read_game(&options.game_file, &options).unwrap().turns;
+        let (board, _) = replay_game(&Board::default(), &r, usize::MAX).unwrap();
+        assert!(board.outcome().is_some());
+
+        let args = ["test_database.pgn", "--black", "Kasparov"];
+        let options = Options::from_args(args.iter().map(|a| a.to_string())).unwrap();
+        assert!(matches!(
+            read_game(&options.game_file, &options),
+            Err(SjakkError::NoSuchGame)
+        ));
+    }
+
+    #[test]
+    fn test_bad_options() {
+        let error = |args: &[&str]| {
+            Options::from_args(args.iter().map(|a| a.to_string()))
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error(&["game.pgn", "--game", "abc"]),
+            "not a game number 'abc'"
+        );
+        assert_eq!(error(&["game.pgn", "--ply"]), "not a ply count ''");
+        assert_eq!(error(&["game.pgn", "--white"]), "missing player name ''");
+        assert_eq!(error(&["game.pgn", "--black"]), "missing player name ''");
+        assert_eq!(error(&["game.pgn", "12x"]), "not a round '12x'");
+        assert_eq!(
+            error(&["game.pgn", "--ply", "7", "12w"]),
+            "can't use --ply together with the round '12w'"
+        );
+    }
+
+    #[test]
+    fn test_style_options() {
+        let args = ["game.pgn", "--flip", "12w", "--ascii"];
+        let options = Options::from_args(args.iter().map(|a| a.to_string())).unwrap();
+
+        assert_eq!(
+            options.style,
+            DiagramStyle {
+                flipped: true,
+                coordinates: true,
+                ascii: true,
+            }
+        );
+        assert_eq!(options.game_file, "game.pgn");
+
+        let options = Options::from_args(["--no-coordinates".to_string()].into_iter()).unwrap();
+        assert!(!options.style.coordinates && !options.style.flipped);
+    }
+
+    #[test]
+    fn test_position_state() {
+        let turns = [
+            Turn::new_from_notation("e4 c5").unwrap(),
+            Turn::new_from_notation("Nf3 d6").unwrap(),
+            Turn::new_from_notation("Rg1 Nf6").unwrap(),
+        ];
+        let board = turns
+            .iter()
+            .take(1)
+            .flat_map(Turn::plies)
+            .try_fold(Board::default(), make_a_ply)
+            .unwrap();
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2"
+        );
+
+        let board = turns
+            .iter()
+            .flat_map(Turn::plies)
+            .try_fold(Board::default(), make_a_ply)
+            .unwrap();
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkb1r/pp2pppp/3p1n2/2p5/4P3/5N2/PPPP1PPP/RNBQKBR1 w Qkq - 2 4"
+        );
+    }
+
+    #[test]
+    fn test_ply_target() {
+        let game = read_game("bobby_game.txt", &Options::default()).unwrap();
+
+        assert_eq!(Target::from_notation("12w").unwrap().ply_count(&game), 23);
+        assert_eq!(Target::from_notation("12b").unwrap().ply_count(&game), 24);
+        assert_eq!(Target::from_notation("12").unwrap().ply_count(&game), 24);
+        assert_eq!(Target::from_notation("56b").unwrap().ply_count(&game), 111);
+        assert_eq!(Target::from_notation("0").unwrap().ply_count(&game), 0);
+        assert_eq!(Target::from_notation("0w").unwrap().ply_count(&game), 0);
+        assert_eq!(Target::Ply(7).ply_count(&game), 7);
+        assert!(Target::from_notation("w").is_none());
+
+        let board = game
+            .turns
+            .iter()
+            .flat_map(Turn::plies)
+            .take(Target::from_notation("1w").unwrap().ply_count(&game))
+            .try_fold(Board::default(), make_a_ply)
+            .unwrap();
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+    }
+
+    #[test]
+    fn test_validate_checks() {
+        let turns = PgnGame::parse("1. e4 e5 2. Bc4 Nc6 3. Qh5+ Nf6 4. Qxf7 1-0")
+            .unwrap()
+            .turns;
+        let (corrected, mismatches) = validate_checks(&Board::default(), &turns).unwrap();
+
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].to_string(), "3. Qh5+ should be Qh5");
+        assert_eq!(mismatches[1].to_string(), "4. Qxf7 should be Qxf7#");
+        assert_eq!(corrected[2], Turn::new_from_notation("Qh5 Nf6").unwrap());
+        assert_eq!(corrected[3].to_string(), "Qxf7#");
+
+        let (_, mismatches) = validate_checks(&Board::default(), &corrected).unwrap();
+        assert!(mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_setup_position() {
+        let game = PgnGame::parse(
+            "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 30\"]\n\n30. e4+ Kd7 31. e5 *",
+        )
+        .unwrap();
+
+        let (board, _) = replay_game(&game.start, &game.turns, usize::MAX).unwrap();
+        assert_eq!(board.to_fen(), "8/3k4/8/4P3/8/8/8/4K3 b - - 0 31");
+
+        let (_, mismatches) = validate_checks(&game.start, &game.turns).unwrap();
+        assert_eq!(mismatches[0].to_string(), "30. e4+ should be e4");
+
+        assert_eq!(Target::from_notation("30").unwrap().ply_count(&game), 2);
+        assert_eq!(Target::from_notation("31w").unwrap().ply_count(&game), 3);
+        assert_eq!(Target::from_notation("12").unwrap().ply_count(&game), 0);
+
+        let start = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+        let game = read_uci_game("e8d7 e2e4", start).unwrap();
+        assert_eq!(game.turns[0].value[0], None);
+        assert_eq!(game.turns[0].value[1].unwrap().to_string(), "Kd7");
+        assert_eq!(game.start.side_to_move, Colour::Black);
+
+        let args = ["game.pgn", "--start", "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1"];
+        let options = Options::from_args(args.iter().map(|a| a.to_string())).unwrap();
+        assert_eq!(options.start.unwrap().side_to_move, Colour::Black);
+    }
+
+    #[test]
+    fn test_promotion() {
+        let opening = "1. h4 g5 2. hxg5 Nf6 3. gxf6 h6 4. fxe7 h5 5.";
+        let replay = |movetext: String| {
+            PgnGame::parse(movetext)
+                .unwrap()
+                .turns
+                .iter()
+                .flat_map(Turn::plies)
+                .try_fold(Board::default(), make_a_ply)
+                .unwrap()
+        };
+
+        for (promotion, piece_type) in [
+            ("exd8=Q+", PieceType::Queen(Colour::White)),
+            ("exd8Q+", PieceType::Queen(Colour::White)),
+            ("exf8=N", PieceType::Knight(Colour::White)),
+            ("exf8R", PieceType::Rook(Colour::White)),
+        ] {
+            let board = replay(format!("{opening} {promotion}"));
+            let to = Move::new_from_notation(promotion, 'w').unwrap().to.unwrap();
+
+            assert_eq!(board.piece_at(to), Some(piece_type));
+            assert_eq!(board.piece_at("e7".parse().unwrap()), None);
+        }
+    }
+
+    #[test]
+    fn test_en_passant() {
+        for movetext in [
+            "1. e4 a6 2. e5 d5 3. exd6 e.p. Qxd6",
+            "1. e4 a6 2. e5 d5 3. exd6 Qxd6",
+        ] {
+            let turns = PgnGame::parse(movetext).unwrap().turns;
+            let plies: Vec<Move> = turns.iter().flat_map(Turn::plies).collect();
+            let board = plies
+                .iter()
+                .take(5)
+                .copied()
+                .try_fold(Board::default(), make_a_ply)
+                .unwrap();
+
+            assert_eq!(plies.len(), 6);
+            assert_eq!(board.piece_at("d5".parse().unwrap()), None);
+            assert_eq!(
+                board.piece_at("d6".parse().unwrap()),
+                Some(PieceType::Pawn(Colour::White))
+            );
+            assert_eq!(board.pieces().count(), 31);
+            assert_eq!(board.en_passant, None);
+
+            let board = make_a_ply(board, plies[5]).unwrap();
+            assert_eq!(
+                board.piece_at("d6".parse().unwrap()),
+                Some(PieceType::Queen(Colour::Black))
+            );
+        }
+    }
+
+    #[test]
+    fn test_castling() {
+        let replay = |movetext: &str| {
+            PgnGame::parse(movetext)
+                .unwrap()
+                .turns
+                .iter()
+                .flat_map(Turn::plies)
+                .try_fold(Board::default(), make_a_ply)
+        };
+
+        let board = replay("1. e4 e5 2. Nf3 Nf6 3. Bc4 Bc5 4. O-O O-O").unwrap();
+        assert_eq!(
+            board.piece_at("g1".parse().unwrap()),
+            Some(PieceType::King(Colour::White))
+        );
+        assert_eq!(
+            board.piece_at("f8".parse().unwrap()),
+            Some(PieceType::Rook(Colour::Black))
+        );
+        assert_eq!(board.castling, types::CastlingRights::default());
+
+        // The king has moved, so castling later is illegal even from the right squares.
+        assert!(replay("1. e4 e5 2. Ke2 Ke7 3. Ke1 Ke8 4. Nf3 Nf6 5. Bc4 Bc5 6. O-O").is_err());
+        // The rook on h1 has moved.
+        assert!(replay("1. e4 e5 2. Nf3 Nf6 3. Bc4 Bc5 4. Rg1 Rg8 5. Rh1 Rh8 6. O-O").is_err());
+        // The bishop on f1 is still in the way.
+        assert!(replay("1. e4 e5 2. Nf3 Nf6 3. O-O").is_err());
+
+        let through_check = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+        assert!(make_a_ply(
+            through_check.clone(),
+            Move::new_from_notation("O-O", 'w').unwrap()
+        )
+        .is_err());
+
+        let board = make_a_ply(
+            through_check,
+            Move::new_from_notation("O-O-O", 'w').unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            board.piece_at("c1".parse().unwrap()),
+            Some(PieceType::King(Colour::White))
+        );
+        assert_eq!(
+            board.piece_at("d1".parse().unwrap()),
+            Some(PieceType::Rook(Colour::White))
+        );
     }
 
     #[test]
@@ -57,152 +353,665 @@ mod tests {
         let temp_move_4 = Move::new_from_notation("Bg7", 'b').unwrap();
         let mut mock_board = Board::default();
 
-        assert_eq!(find_moved_piece(&mut mock_board, temp_move_1).unwrap(), 24);
-        assert_eq!(find_moved_piece(&mut mock_board, temp_move_2).unwrap(), 4);
-        assert_eq!(find_moved_piece(&mut mock_board, temp_move_3).unwrap(), 29);
-        mock_board.pieces.remove(29);
+        assert_eq!(
+            mock_board.resolve(&temp_move_1).unwrap().from,
+            "e2".parse().unwrap()
+        );
+        assert_eq!(
+            mock_board.resolve(&temp_move_2).unwrap().from,
+            "b1".parse().unwrap()
+        );
+
+        mock_board.side_to_move = Colour::Black;
+        assert_eq!(
+            mock_board.resolve(&temp_move_3).unwrap().from,
+            "g7".parse().unwrap()
+        );
+        mock_board.remove_piece("g7".parse().unwrap());
 
-        assert_eq!(find_moved_piece(&mut mock_board, temp_move_4).unwrap(), 11);
+        assert_eq!(
+            mock_board.resolve(&temp_move_4).unwrap().from,
+            "f8".parse().unwrap()
+        );
     }
 
     #[test]
-    fn test_make_a_move() {
+    fn test_pinned_piece() {
+        // The knight on c3 is pinned by the bishop on b4, so only the g1 knight can go to e2.
+        let turns = PgnGame::parse("1. d4 e6 2. e3 Bb4+ 3. Nc3 Nf6 4. Ne2")
+            .unwrap()
+            .turns;
+        let (board, _) = replay_game(&Board::default(), &turns, usize::MAX).unwrap();
+
+        assert_eq!(
+            board.piece_at("e2".parse().unwrap()),
+            Some(PieceType::Knight(Colour::White))
+        );
+        assert_eq!(
+            board.piece_at("c3".parse().unwrap()),
+            Some(PieceType::Knight(Colour::White))
+        );
+        assert_eq!(board.piece_at("g1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        let board = Board::from_fen("4k3/8/8/8/4Q2Q/8/8/K6Q w - - 0 1").unwrap();
+        let play = |notation: &str| {
+            make_a_ply(
+                board.clone(),
+                Move::new_from_notation(notation, 'w').unwrap(),
+            )
+        };
+
+        for ambiguous in ["Qe1", "Qhe1", "Q4e1"] {
+            assert!(matches!(
+                play(ambiguous),
+                Err(SjakkError::AmbiguousMove { .. })
+            ));
+        }
+
+        let after = play("Qh4e1").unwrap();
+        assert_eq!(after.piece_at("h4".parse().unwrap()), None);
+        assert_eq!(
+            after.piece_at("e1".parse().unwrap()),
+            Some(PieceType::Queen(Colour::White))
+        );
+
+        let board = Board::from_fen("4k3/8/8/8/8/4R3/8/K3R3 w - - 0 1").unwrap();
+        let after =
+            make_a_ply(board.clone(), Move::new_from_notation("R1e2", 'w').unwrap()).unwrap();
+        assert_eq!(after.piece_at("e1".parse().unwrap()), None);
+        assert_eq!(
+            after.piece_at("e3".parse().unwrap()),
+            Some(PieceType::Rook(Colour::White))
+        );
+        assert!(make_a_ply(board, Move::new_from_notation("Re2", 'w').unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_uci_game() {
+        let game =
+            read_uci_game("e2e4 e7e5\ng1f3 b8c6 f1b5 a7a6 e1g1 1-0", Board::default()).unwrap();
+
+        assert_eq!(game.turns.len(), 4);
+        assert_eq!(game.turns[3], Turn::new_from_notation("O-O").unwrap());
+        assert_eq!(game.result, GameResult::WhiteWins);
+        assert_eq!(
+            replay_game(&game.start, &game.turns, usize::MAX)
+                .unwrap()
+                .0
+                .pieces()
+                .count(),
+            32
+        );
+
+        assert_eq!(
+            read_uci_game("e2e4 e7e5\ng1f3 b8c7", Board::default())
+                .unwrap_err()
+                .to_string(),
+            "ply 4: b8c7 is illegal, no legal move goes between these squares"
+        );
+        assert_eq!(
+            read_uci_game("e2e4 e7e5\ng1f3 b8", Board::default())
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 6: not a UCI move 'b8'"
+        );
+    }
+
+    #[test]
+    fn test_draw_by_repetition() {
+        let replay = |movetext: &str| {
+            let game = PgnGame::parse(movetext).unwrap();
+            replay_game(&game.start, &game.turns, usize::MAX).unwrap().1
+        };
+
+        assert_eq!(replay("1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6"), None);
+        assert_eq!(
+            replay("1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 Ng8 5. e4"),
+            Some((DrawRule::ThreefoldRepetition, 4))
+        );
+    }
+
+    #[test]
+    fn test_interactive() {
+        let game = PgnGame::parse("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6").unwrap();
+        let replayer =
+            Replayer::new(Board::default(), game.turns.iter().flat_map(Turn::plies)).unwrap();
+        let input = "n\n\nx\np\ng 2w\ne\ns\n\u{1b}[C\nq\nn\n";
+        let mut output = vec![];
+
+        interactive(replayer, &game, input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let screens: Vec<&str> = output
+            .lines()
+            .filter(|l| l.starts_with("Last move") || l.starts_with("Start"))
+            .collect();
+
+        assert_eq!(
+            screens,
+            [
+                "Start position",
+                "Last move: 1. e4",
+                "Last move: 1... e5",
+                "Last move: 1. e4",
+                "Last move: 2. Nf3",
+                "Last move: 3... a6",
+                "Start position",
+                "Last move: 1. e4",
+            ]
+        );
+        assert_eq!(output.matches(HELP).count(), 2);
+        assert!(output.contains("1. e4 e5 2. [Nf3] Nc6 3. Bb5 a6"));
+    }
+
+    #[test]
+    fn test_replay_errors() {
+        let replay = |movetext: &str| {
+            let game = PgnGame::parse(movetext).unwrap();
+            replay_game(&game.start, &game.turns, usize::MAX)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            replay("1. Nf3 Nf6 2. Nc3 Nc6 3. Nd4 Nd5 4. Nb5"),
+            "ply 7: Nb5 is ambiguous, it can be played from c3, d4"
+        );
+        assert_eq!(replay("1. e4 e5 2. Qe3"), "ply 3: no piece can play Qe3");
+        assert_eq!(
+            replay("1. e4 e5 2. O-O"),
+            "ply 3: O-O is illegal, castling is not allowed in this position"
+        );
+    }
+
+    #[test]
+    fn test_make_a_ply() {
         let default_board = Board::default();
         let mut after_move_board = Board::default();
 
-        let i = after_move_board.find_piece_by_pos(4, 1).unwrap();
-        after_move_board.pieces.remove(i);
-
-        let i = after_move_board.find_piece_by_pos(6, 6).unwrap();
-        after_move_board.pieces.remove(i);
+        after_move_board.remove_piece("e2".parse().unwrap());
+        after_move_board.remove_piece("g7".parse().unwrap());
 
-        after_move_board.pieces.push(Piece::new(
-            [4, 3],
-            PieceType::Pawn(Colour::White),
-        ));
-        after_move_board.pieces.push(Piece::new(
-            [6, 5],
-            PieceType::Pawn(Colour::Black),
-        ));
+        after_move_board.put_piece("e4".parse().unwrap(), PieceType::Pawn(Colour::White));
+        after_move_board.put_piece("g6".parse().unwrap(), PieceType::Pawn(Colour::Black));
 
         assert_eq!(
             after_move_board,
-            make_a_move(default_board, &Turn::new_from_notation("e4 g6").unwrap())
+            Turn::new_from_notation("e4 g6")
+                .unwrap()
+                .plies()
+                .try_fold(default_board, make_a_ply)
+                .unwrap()
         );
     }
 }
 
-fn find_moved_piece(board: &mut Board, mv: Move) -> Option<usize> {
-    let piece_type_equality = |p: &&Piece| p.piece_type == mv.piece;
+fn make_a_ply(mut board: Board, mv: Move) -> Result<Board, SjakkError> {
+    if mv.castle != types::Castle::No && !board.can_castle(mv.castle) {
+        return Err(SjakkError::IllegalMove {
+            notation: mv.to_string(),
+            reason: "castling is not allowed in this position".to_string(),
+            ply: None,
+        });
+    }
 
-    let legal_moves_equality = |p: &&Piece| match mv.capture {
-        Capture::Yes => p.get_capture_tiles(mv.castle).contains(&mv.to.unwrap()),
-        Capture::No => p.get_move_tiles(mv.castle).contains(&mv.to.unwrap()),
-    };
+    let resolved = board.resolve(&mv)?;
+    board.make_move(&resolved);
 
-    let ambiguity_remover = |p: &&Piece| {
-        if let Some(x) = mv.from {
-            p.pos[0] == x as i8
-        } else {
-            true
+    Ok(board)
+}
+
+/// A move whose check annotation disagrees with the position it leads to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CheckMismatch {
+    round: usize,
+    annotated: Move,
+    actual: Check,
+}
+
+impl Display for CheckMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dots = match self.annotated.piece.get_colour() {
+            Colour::White => ".",
+            Colour::Black => "...",
+        };
+        let corrected = Move {
+            check: self.actual,
+            ..self.annotated
+        };
+
+        write!(
+            f,
+            "{}{dots} {} should be {corrected}",
+            self.round, self.annotated
+        )
+    }
+}
+
+/// Replays the game from `start` and compares every `+` and `#` with the position after
+/// the move. Returns the game with all annotations corrected, along with the moves that
+/// were wrong.
+fn validate_checks(
+    start: &Board,
+    turns: &[Turn],
+) -> Result<(Vec<Turn>, Vec<CheckMismatch>), SjakkError> {
+    let mut board = start.clone();
+    let mut corrected = turns.to_vec();
+    let mut mismatches = vec![];
+    let mut ply = 0;
+
+    for (i, turn) in corrected.iter_mut().enumerate() {
+        for mv in turn.value.iter_mut().flatten() {
+            ply += 1;
+            board = make_a_ply(board, *mv).map_err(|e| e.at_ply(ply))?;
+            let actual = board.check_annotation();
+
+            if mv.check != actual {
+                mismatches.push(CheckMismatch {
+                    round: start.fullmove_number as usize + i,
+                    annotated: *mv,
+                    actual,
+                });
+                mv.check = actual;
+            }
         }
-    };
+    }
 
-    let blocking_piece_checker = |p: &&Piece| match p.piece_type {
-        PieceType::Pawn(_) | PieceType::King(_) | PieceType::Knight(_) => true,
-        _ => {
-            let mut temp_board: Board = board.clone();
+    Ok((corrected, mismatches))
+}
 
-            !temp_board.check_for_collisions(p.pos, mv.to.unwrap(), mv.piece)
+/// How far into the game to replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    /// Up to and including the given colour's move in the given round, e.g. "12w".
+    Round(usize, Colour),
+    /// The given number of plies (half-moves) from the start.
+    Ply(usize),
+}
+
+impl Target {
+    fn from_notation(value: &str) -> Option<Self> {
+        let (round, colour) = match value.strip_suffix(['w', 'b']) {
+            Some(round) if value.ends_with('w') => (round, Colour::White),
+            Some(round) => (round, Colour::Black),
+            None => (value, Colour::Black),
+        };
+
+        Some(Self::Round(round.parse().ok()?, colour))
+    }
+
+    fn ply_count(self, game: &PgnGame) -> usize {
+        match self {
+            Self::Ply(n) => n,
+            Self::Round(round, colour) => {
+                // Rounds before the game's first one, such as round 0, are its starting position.
+                let Some(index) = round.checked_sub(game.start.fullmove_number as usize) else {
+                    return 0;
+                };
+                let before: usize = game
+                    .turns
+                    .iter()
+                    .take(index)
+                    .map(|t| t.plies().count())
+                    .sum();
+                let within = match (game.turns.get(index), colour) {
+                    (Some(turn), Colour::White) => turn.value[0].iter().count(),
+                    (Some(turn), Colour::Black) => turn.plies().count(),
+                    (None, _) => 0,
+                };
+
+                before + within
+            }
         }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    game_file: String,
+    target: Option<Target>,
+    game_number: Option<usize>,
+    white: Option<String>,
+    black: Option<String>,
+    start: Option<Board>,
+    fen: bool,
+    validate: bool,
+    fix: bool,
+    pgn: bool,
+    uci: bool,
+    interactive: bool,
+    style: DiagramStyle,
+}
+
+impl Options {
+    fn from_args<I>(mut args: I) -> Result<Self, SjakkError>
+    where
+        I: Iterator<Item = String>,
+    {
+        let mut options = Self::default();
+        let mut positional: Vec<String> = vec![];
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--game" => options.game_number = Some(number_arg(&mut args, "not a game number")?),
+                "--white" => options.white = Some(name_arg(&mut args)?),
+                "--black" => options.black = Some(name_arg(&mut args)?),
+                "--start" => {
+                    options.start = Some(Board::from_fen(args.next().unwrap_or_default())?)
+                }
+                "--fen" => options.fen = true,
+                "--validate" => options.validate = true,
+                "--fix" => options.fix = true,
+                "--pgn" => options.pgn = true,
+                "--uci" => options.uci = true,
+                "--interactive" => options.interactive = true,
+                "--flip" => options.style.flipped = true,
+                "--no-coordinates" => options.style.coordinates = false,
+                "--ascii" => options.style.ascii = true,
+                "--ply" => {
+                    options.target = Some(Target::Ply(number_arg(&mut args, "not a ply count")?))
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        options.game_file = positional.next().unwrap_or_default();
+        if let Some(round) = positional.next() {
+            let target = Target::from_notation(&round)
+                .ok_or_else(|| SjakkError::parse(&round, "not a round"))?;
+
+            if options.target.is_some() {
+                return Err(SjakkError::parse(
+                    round,
+                    "can't use --ply together with the round",
+                ));
+            }
+            options.target = Some(target);
+        }
+
+        Ok(options)
+    }
+
+    fn selects(&self, entry: &GameEntry) -> bool {
+        let tag_contains = |name: &str, value: &Option<String>| match value {
+            Some(v) => entry.tag(name).is_some_and(|t| t.contains(v.as_str())),
+            None => true,
+        };
+
+        self.game_number.is_none_or(|n| n == entry.number)
+            && tag_contains("White", &self.white)
+            && tag_contains("Black", &self.black)
+    }
+}
+
+/// Reads the number following a flag such as `--game`.
+fn number_arg<I>(args: &mut I, reason: &str) -> Result<usize, SjakkError>
+where
+    I: Iterator<Item = String>,
+{
+    let value = args.next().unwrap_or_default();
+    value.parse().map_err(|_| SjakkError::parse(value, reason))
+}
+
+/// Reads the player name following `--white` or `--black`.
+fn name_arg<I>(args: &mut I) -> Result<String, SjakkError>
+where
+    I: Iterator<Item = String>,
+{
+    args.next()
+        .ok_or_else(|| SjakkError::parse("", "missing player name"))
+}
+
+fn read_game(path: &str, options: &Options) -> Result<PgnGame, SjakkError> {
+    if options.uci {
+        let start = options.start.clone().unwrap_or_default();
+        return read_uci_game(&read_to_string(path)?, start);
+    }
+
+    let database = PgnDatabase::open(path)?;
+
+    for entry in database.entries() {
+        let entry = entry?;
+
+        if options.selects(&entry) {
+            let mut game = entry.game()?;
+            if let Some(start) = &options.start {
+                game.set_start(start.clone())?;
+            }
+            return Ok(game);
+        }
+    }
+
+    Err(SjakkError::NoSuchGame)
+}
+
+/// Reads a game played from `start` written as UCI moves separated by whitespace,
+/// e.g. "e2e4 e7e5 g1f3", optionally followed by a result token.
+fn read_uci_game(text: &str, start: Board) -> Result<PgnGame, SjakkError> {
+    let mut board = start.clone();
+    let mut game = PgnGame {
+        start,
+        ..PgnGame::default()
     };
 
-    let all_possible_capturers: Vec<&Piece> = board
-        .pieces
-        .iter()
-        .filter(piece_type_equality)
-        .filter(legal_moves_equality)
-        .filter(ambiguity_remover)
-        .filter(blocking_piece_checker)
-        .collect();
+    for (i, token) in text.split_whitespace().enumerate() {
+        if let Some(result) = GameResult::from_token(token) {
+            game.result = result;
+            continue;
+        }
+
+        let offset = token.as_ptr() as usize - text.as_ptr() as usize;
+        let resolved = board
+            .parse_uci(token)
+            .map_err(|e| e.at(Location::in_text(text, offset)).at_ply(i + 1))?;
 
-    if all_possible_capturers.len() != 1 {
-        panic!("{} != 1", all_possible_capturers.len());
+        game.push_move(board.san(&resolved));
+        board = board.with_move(&resolved);
     }
 
-    board
-        .pieces
+    Ok(game)
+}
+
+/// Replays the first `ply_count` plies of the game from `start`, returning the board and
+/// the first draw rule that applied on the way with the number of the move that brought
+/// it about.
+fn replay_game(
+    start: &Board,
+    turns: &[Turn],
+    ply_count: usize,
+) -> Result<(Board, Option<(DrawRule, usize)>), SjakkError> {
+    let mut board = start.clone();
+    let mut draw = None;
+
+    for (i, mv) in turns
         .iter()
-        .position(|x| x == all_possible_capturers[0])
+        .flat_map(Turn::plies)
+        .take(ply_count)
+        .enumerate()
+    {
+        let number = board.fullmove_number as usize;
+        board = make_a_ply(board, mv).map_err(|e| e.at_ply(i + 1))?;
+
+        if draw.is_none() {
+            draw = board.draw_rule().map(|rule| (rule, number));
+        }
+    }
+
+    Ok((board, draw))
 }
 
-fn execute_move(mut board: Board, index: usize, to: [i8; 2]) -> Board {
-    let mut moved_piece = board.pieces.remove(index);
-    moved_piece.pos = to;
-    board.pieces.push(moved_piece);
+fn main() -> ExitCode {
+    let mut args = args().skip(1).peekable();
+    let result = match args.peek().map(String::as_str) {
+        Some("perft") => run_perft(args.skip(1)),
+        _ => Options::from_args(args).and_then(run),
+    };
 
-    board
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("sjakk: {error}");
+            ExitCode::FAILURE
+        }
+    }
 }
 
-fn make_a_move(mut board: Board, turn: &Turn) -> Board {
-    for mv in turn.value {
-        // If this is a castle, find and move the Rook.
-        match mv.castle {
-            types::Castle::No => (),
-            types::Castle::Short(pos) => {
-                let castle_index = board.find_piece_by_pos(pos[0], pos[1]).unwrap();
-                let castle_to = [5, pos[1]];
-                board = execute_move(board, castle_index, castle_to);
+const HELP: &str = "Enter or n: next move, p: previous move, g N: go to move N (g 12w stops after \
+                    White's move), s: start, e: end, q: quit. The left and right arrow keys \
+                    followed by Enter step too.";
+
+/// Steps through the game with commands read line by line from `input`, drawing the
+/// position after each one, until `q` or the end of input.
+fn interactive<R, W>(
+    mut replayer: Replayer,
+    game: &PgnGame,
+    input: R,
+    mut output: W,
+) -> Result<(), SjakkError>
+where
+    R: BufRead,
+    W: Write,
+{
+    writeln!(output, "{replayer}\n{HELP}")?;
+
+    for line in input.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words[..] {
+            [] | ["n"] | ["\u{1b}[C"] => {
+                replayer.forward();
+            }
+            ["p"] | ["\u{1b}[D"] => {
+                replayer.back();
             }
-            types::Castle::Long(pos) => {
-                let castle_index = board.find_piece_by_pos(pos[0], pos[1]).unwrap();
-                let castle_to = [3, pos[1]];
-                board = execute_move(board, castle_index, castle_to);
+            ["s"] => replayer.go_to(0),
+            ["e"] => replayer.go_to(replayer.len()),
+            ["g", target] => match Target::from_notation(target) {
+                Some(target) => replayer.go_to(target.ply_count(game)),
+                None => {
+                    writeln!(output, "{HELP}")?;
+                    continue;
+                }
+            },
+            ["q"] => break,
+            _ => {
+                writeln!(output, "{HELP}")?;
+                continue;
             }
         }
 
-        let captured_piece_index: Option<usize> =
-            board.find_piece_by_pos(mv.to.unwrap()[0], mv.to.unwrap()[1]);
+        writeln!(output, "{replayer}")?;
+    }
 
-        if let Some(i) = captured_piece_index {
-            board.pieces.remove(i);
-        }
+    Ok(())
+}
 
-        let moved_piece_index: usize = find_moved_piece(&mut board, mv).unwrap();
-        board = execute_move(board, moved_piece_index, mv.to.unwrap());
+/// `sjakk perft FEN DEPTH`: the nodes below each legal move, then the total.
+fn run_perft<I>(mut args: I) -> Result<(), SjakkError>
+where
+    I: Iterator<Item = String>,
+{
+    let board = Board::from_fen(args.next().unwrap_or_default())?;
+    let depth = args.next().unwrap_or_default();
+    let depth: usize = depth
+        .parse()
+        .map_err(|_| SjakkError::parse(&depth, "not a perft depth"))?;
+
+    let mut divide: Vec<(String, u64)> = board
+        .perft_divide(depth)
+        .iter()
+        .map(|(mv, nodes)| (mv.to_uci(), *nodes))
+        .collect();
+    divide.sort();
+
+    for (mv, nodes) in &divide {
+        println!("{mv}: {nodes}");
     }
+    // Without moves to divide by, depth 0 counts the position itself and a mate counts none.
+    let total: u64 = if divide.is_empty() {
+        board.perft(depth)
+    } else {
+        divide.iter().map(|(_, nodes)| nodes).sum()
+    };
 
-    board
-}
+    println!();
+    println!("Nodes searched: {total}");
 
-fn read_game(path: &str) -> Result<Vec<Turn>, Error> {
-    Ok(read_to_string(path)?
-        .lines()
-        .map(|notation| Turn::new_from_notation(notation))
-        .collect::<Result<Vec<Turn>, Error>>()?)
+    Ok(())
 }
 
-fn main() -> Result<(), Error> {
-    let mut args = args();
+fn run(options: Options) -> Result<(), SjakkError> {
+    let game = read_game(&options.game_file, &options)?;
+    let list_of_turns: Vec<Turn> = game.turns.clone();
+    if options.validate || options.fix {
+        let (corrected, mismatches) = validate_checks(&game.start, &list_of_turns)?;
 
-    let game_file: String = args.nth(1).unwrap_or(String::new());
+        if options.fix {
+            for mismatch in &mismatches {
+                eprintln!("{mismatch}");
+            }
+            if options.pgn {
+                let game = PgnGame {
+                    turns: corrected,
+                    ..game
+                };
+                print!("{}", PgnWriter::from_game(&game));
+            } else {
+                for turn in corrected {
+                    println!("{turn}");
+                }
+            }
+        } else if mismatches.is_empty() {
+            println!("All check annotations agree with the positions.");
+        } else {
+            for mismatch in &mismatches {
+                println!("{mismatch}");
+            }
+        }
+        return Ok(());
+    }
+
+    if options.pgn {
+        print!("{}", PgnWriter::from_game(&game));
+        return Ok(());
+    }
+
+    if options.interactive {
+        let mut replayer = Replayer::new(
+            game.start.clone(),
+            list_of_turns.iter().flat_map(Turn::plies),
+        )?;
+        replayer.set_style(options.style);
+        if let Some(target) = options.target {
+            replayer.go_to(target.ply_count(&game));
+        }
 
-    let game_length: usize = match args.next() {
-        Some(a) => match a.parse::<usize>() {
-            Ok(a) => a,
-            Err(_) => usize::MAX,
-        },
+        return interactive(replayer, &game, stdin().lock(), stdout());
+    }
+
+    let ply_count = match options.target {
+        Some(target) => target.ply_count(&game),
         None => usize::MAX,
     };
 
-    let list_of_turns: Vec<Turn> = read_game(&game_file).unwrap_or(vec![]);
-    let initial_board: Board = Board::default();
+    let (final_board, draw) = replay_game(&game.start, &list_of_turns, ply_count)?;
 
-    let final_board: Board = list_of_turns
-        .iter()
-        .take(game_length)
-        .fold(initial_board, |b, t| make_a_move(b, t));
+    if options.fen {
+        println!("{}", final_board.to_fen());
+    } else {
+        println!("{}", final_board.diagram(options.style));
 
-    println!("{}", final_board);
+        if let Some((rule, number)) = draw {
+            println!("Draw by {rule} at move {number}");
+        }
+
+        if let Some(outcome) = final_board.outcome() {
+            println!("{outcome}");
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,543 @@
+use crate::{
+    bitboard::{
+        bishop_attacks, king_attacks, knight_attacks, pawn_attacks, queen_attacks, rook_attacks,
+        Bitboard,
+    },
+    board::Board,
+    piece::Piece,
+    square::{File, Rank, Square},
+    types::{Capture, Castle, CastlingRights, Check, Colour, Outcome, PieceType, Promotion},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::sq;
+
+    fn count_moves(fen: &str) -> usize {
+        Board::from_fen(fen).unwrap().legal_moves().len()
+    }
+
+    #[test]
+    fn test_known_positions() {
+        assert_eq!(Board::default().legal_moves().len(), 20);
+        assert_eq!(
+            count_moves("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+            48
+        );
+        assert_eq!(count_moves("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"), 14);
+        assert_eq!(
+            count_moves("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"),
+            6
+        );
+        assert_eq!(
+            count_moves("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8"),
+            44
+        );
+    }
+
+    #[test]
+    fn test_pinned_piece() {
+        // The knight on e2 is pinned by the rook on e8 and may not move at all.
+        let board = Board::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+
+        assert!(board.legal_moves().iter().all(|m| m.from != sq("e2")));
+    }
+
+    #[test]
+    fn test_check_evasion() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/3q4/4K3 w - - 0 1").unwrap();
+        let moves = board.legal_moves();
+
+        // Only Kxd2 and Kf1 get out of check.
+        assert_eq!(moves.len(), 2);
+        assert!(moves
+            .iter()
+            .any(|m| m.to == sq("d2") && m.capture == Capture::Yes));
+        assert!(moves.iter().any(|m| m.to == sq("f1")));
+    }
+
+    #[test]
+    fn test_en_passant() {
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+        let en_passant: Vec<ResolvedMove> = board
+            .legal_moves()
+            .into_iter()
+            .filter(|m| m.en_passant)
+            .collect();
+
+        assert_eq!(en_passant.len(), 1);
+
+        let after = board.with_move(&en_passant[0]);
+        assert!(after.piece_at(sq("d5")).is_none());
+        assert!(after.piece_at(sq("d6")).is_some());
+    }
+
+    #[test]
+    fn test_castling() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let castles = |b: &Board| {
+            b.legal_moves()
+                .iter()
+                .filter(|m| m.castle != Castle::No)
+                .count()
+        };
+
+        assert_eq!(castles(&board), 2);
+
+        // The f1 square is attacked, so only the long castle remains.
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(castles(&board), 1);
+
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1").unwrap();
+        let after = board.with_move(
+            board
+                .legal_moves()
+                .iter()
+                .find(|m| m.castle != Castle::No)
+                .unwrap(),
+        );
+        assert!(after.piece_at(sq("f1")).is_some());
+        assert!(after.piece_at(sq("h1")).is_none());
+        assert!(!after.castling.white_short);
+    }
+
+    #[test]
+    fn test_make_and_unmake() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2",
+        ] {
+            let mut board = Board::from_fen(fen).unwrap();
+
+            for mv in board.legal_moves() {
+                let undo = board.make_move(&mv);
+                board.unmake_move(undo);
+                assert_eq!(board.to_fen(), fen, "after taking back {mv:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_terminal_positions() {
+        let start = Board::default();
+        assert!(!start.is_check() && !start.is_checkmate() && !start.is_stalemate());
+        assert_eq!(start.outcome(), None);
+
+        let fools_mate =
+            Board::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        assert!(fools_mate.is_check() && fools_mate.is_checkmate());
+        assert_eq!(
+            fools_mate.outcome(),
+            Some(Outcome::Checkmate(Colour::Black))
+        );
+
+        let check = Board::from_fen("4k3/8/8/8/8/8/3q4/4K3 w - - 0 1").unwrap();
+        assert!(check.is_check() && !check.is_checkmate());
+
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(stalemate.is_stalemate() && !stalemate.is_check());
+        assert_eq!(stalemate.outcome(), Some(Outcome::Stalemate));
+    }
+
+    #[test]
+    fn test_can_castle() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+        assert!(!board.can_castle(Castle::Short(sq("h1"))));
+        assert!(board.can_castle(Castle::Long(sq("a1"))));
+
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/RN2K2R w Kkq - 0 1").unwrap();
+        assert!(board.can_castle(Castle::Short(sq("h1"))));
+        assert!(!board.can_castle(Castle::Long(sq("a1"))));
+
+        // Black may not castle while it is White's move.
+        assert!(!board.can_castle(Castle::Short(sq("h8"))));
+    }
+
+    #[test]
+    fn test_promotion() {
+        let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotions = board
+            .legal_moves()
+            .into_iter()
+            .filter(|m| m.promotion != Promotion::No)
+            .count();
+
+        assert_eq!(promotions, 4);
+    }
+}
+
+/// A move with every detail filled in, as produced by the move generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedMove {
+    pub piece: PieceType,
+    pub from: Square,
+    pub to: Square,
+    pub capture: Capture,
+    pub promotion: Promotion,
+    pub castle: Castle,
+    pub en_passant: bool,
+}
+
+impl ResolvedMove {
+    fn new(piece: PieceType, from: Square, to: Square, capture: Capture) -> Self {
+        Self {
+            piece,
+            from,
+            to,
+            capture,
+            promotion: Promotion::No,
+            castle: Castle::No,
+            en_passant: false,
+        }
+    }
+}
+
+/// What `Board::make_move` needs to take a move back: the move itself, the piece it
+/// captured and the state that can't be worked out from the move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndoInfo {
+    pub mv: ResolvedMove,
+    pub captured: Option<PieceType>,
+    pub castling: CastlingRights,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+/// The square of the piece `mv` captures, if it captures one.
+fn captured_square(mv: &ResolvedMove) -> Square {
+    if mv.en_passant {
+        Square::new(mv.to.file(), mv.from.rank())
+    } else {
+        mv.to
+    }
+}
+
+/// Where the rook of `castle` starts and ends.
+fn rook_squares(castle: Castle) -> Option<(Square, Square)> {
+    match castle {
+        Castle::Short(rook) => Some((rook, Square::new(File::F, rook.rank()))),
+        Castle::Long(rook) => Some((rook, Square::new(File::D, rook.rank()))),
+        Castle::No => None,
+    }
+}
+
+impl Board {
+    pub fn king_pos(&self, colour: Colour) -> Option<Square> {
+        self.bitboard(PieceType::King(colour)).first()
+    }
+
+    /// The squares of the pieces of colour `by` that attack `pos`.
+    pub fn attackers(&self, pos: Square, by: Colour) -> Bitboard {
+        let occupied = self.occupied();
+        let queens = self.bitboard(PieceType::Queen(by));
+
+        (pawn_attacks(pos, by.opposite()) & self.bitboard(PieceType::Pawn(by)))
+            | (knight_attacks(pos) & self.bitboard(PieceType::Knight(by)))
+            | (king_attacks(pos) & self.bitboard(PieceType::King(by)))
+            | (rook_attacks(pos, occupied) & (self.bitboard(PieceType::Rook(by)) | queens))
+            | (bishop_attacks(pos, occupied) & (self.bitboard(PieceType::Bishop(by)) | queens))
+    }
+
+    /// Whether any piece of colour `by` attacks `pos`.
+    pub fn is_attacked(&self, pos: Square, by: Colour) -> bool {
+        !self.attackers(pos, by).is_empty()
+    }
+
+    /// Whether the king of `colour` is attacked. A board without that king is never in check.
+    pub fn is_king_attacked(&self, colour: Colour) -> bool {
+        self.king_pos(colour)
+            .is_some_and(|pos| self.is_attacked(pos, colour.opposite()))
+    }
+
+    /// Whether the side to move is in check.
+    pub fn is_check(&self) -> bool {
+        self.is_king_attacked(self.side_to_move)
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && self.legal_moves().is_empty()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_check() && self.legal_moves().is_empty()
+    }
+
+    /// The check annotation a move leading to this position should carry.
+    pub fn check_annotation(&self) -> Check {
+        if self.is_checkmate() {
+            Check::Mate
+        } else if self.is_check() {
+            Check::Check
+        } else {
+            Check::No
+        }
+    }
+
+    /// The outcome when the side to move has no legal moves left, or when a draw rule
+    /// ends the game without a claim.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.legal_moves().is_empty() {
+            self.draw_rule()
+                .filter(|rule| rule.is_automatic())
+                .map(Outcome::Draw)
+        } else if self.is_check() {
+            Some(Outcome::Checkmate(self.side_to_move.opposite()))
+        } else {
+            Some(Outcome::Stalemate)
+        }
+    }
+
+    /// Whether the side to move may castle this way: the rights are intact, the path between
+    /// king and rook is empty and the king neither starts in, passes through nor lands in check.
+    pub fn can_castle(&self, castle: Castle) -> bool {
+        let Some(king) = self.king_pos(self.side_to_move) else {
+            return false;
+        };
+
+        let mut moves = vec![];
+        self.castling_moves(king, &mut moves);
+
+        moves.iter().any(|m| m.castle == castle)
+    }
+
+    /// Every legal move for the side to move.
+    pub fn legal_moves(&self) -> Vec<ResolvedMove> {
+        let colour = self.side_to_move;
+        let mut board = self.clone();
+
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|mv| {
+                let undo = board.make_move(mv);
+                let is_legal = !board.is_king_attacked(colour);
+                board.unmake_move(undo);
+                is_legal
+            })
+            .collect()
+    }
+
+    /// The board after playing `mv`, which is assumed to be legal.
+    pub fn with_move(&self, mv: &ResolvedMove) -> Self {
+        let mut board = self.clone();
+        board.make_move(mv);
+        board
+    }
+
+    /// Plays `mv`, which is assumed to be legal, on this board. The returned `UndoInfo`
+    /// takes it back again with `unmake_move`.
+    pub fn make_move(&mut self, mv: &ResolvedMove) -> UndoInfo {
+        let undo = UndoInfo {
+            mv: *mv,
+            captured: self.piece_at(captured_square(mv)),
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        };
+
+        self.history.push(self.zobrist());
+        self.remove_piece(captured_square(mv));
+        if let Some((rook, rook_to)) = rook_squares(mv.castle) {
+            self.move_piece(rook, rook_to);
+        }
+
+        self.record_move(mv.piece, mv.from, mv.to, mv.capture == Capture::Yes);
+        self.move_piece(mv.from, mv.to);
+
+        if let Promotion::Yes(piece_type) = mv.promotion {
+            self.put_piece(mv.to, piece_type);
+        }
+
+        undo
+    }
+
+    /// Takes back the move `undo` was returned for, which must be the last move made.
+    pub fn unmake_move(&mut self, undo: UndoInfo) {
+        let mv = undo.mv;
+
+        self.remove_piece(mv.to);
+        self.put_piece(mv.from, mv.piece);
+
+        if let Some((rook, rook_to)) = rook_squares(mv.castle) {
+            self.move_piece(rook_to, rook);
+        }
+        if let Some(captured) = undo.captured {
+            self.put_piece(captured_square(&mv), captured);
+        }
+
+        self.side_to_move = mv.piece.get_colour();
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.history.pop();
+    }
+
+    fn pseudo_legal_moves(&self) -> Vec<ResolvedMove> {
+        let colour = self.side_to_move;
+        let occupied = self.occupied();
+        let mut moves = vec![];
+
+        for piece in self
+            .pieces()
+            .filter(|p| p.piece_type.get_colour() == colour)
+        {
+            let targets = match piece.piece_type {
+                PieceType::Pawn(_) => {
+                    self.pawn_moves(&piece, &mut moves);
+                    continue;
+                }
+                PieceType::Knight(_) => knight_attacks(piece.pos),
+                PieceType::King(_) => {
+                    self.castling_moves(piece.pos, &mut moves);
+                    king_attacks(piece.pos)
+                }
+                PieceType::Bishop(_) => bishop_attacks(piece.pos, occupied),
+                PieceType::Rook(_) => rook_attacks(piece.pos, occupied),
+                PieceType::Queen(_) => queen_attacks(piece.pos, occupied),
+                PieceType::Empty(_) => continue,
+            };
+
+            let enemies = self.colour(colour.opposite());
+            for to in (targets & !self.colour(colour)).squares() {
+                let capture = if enemies.contains(to) {
+                    Capture::Yes
+                } else {
+                    Capture::No
+                };
+                moves.push(ResolvedMove::new(piece.piece_type, piece.pos, to, capture));
+            }
+        }
+
+        moves
+    }
+
+    fn pawn_moves(&self, piece: &Piece, moves: &mut Vec<ResolvedMove>) {
+        let colour = piece.piece_type.get_colour();
+        let (direction, start_rank, last_rank) = match colour {
+            Colour::White => (1, Rank::SECOND, Rank::EIGHTH),
+            Colour::Black => (-1, Rank::SEVENTH, Rank::FIRST),
+        };
+        let is_empty = |pos: &Square| !self.occupied().contains(*pos);
+
+        let mut targets: Vec<ResolvedMove> = vec![];
+
+        if let Some(to) = piece.pos.offset(0, direction).filter(is_empty) {
+            targets.push(ResolvedMove::new(
+                piece.piece_type,
+                piece.pos,
+                to,
+                Capture::No,
+            ));
+
+            if let Some(to) = to
+                .offset(0, direction)
+                .filter(|p| piece.pos.rank() == start_rank && is_empty(p))
+            {
+                targets.push(ResolvedMove::new(
+                    piece.piece_type,
+                    piece.pos,
+                    to,
+                    Capture::No,
+                ));
+            }
+        }
+
+        for to in pawn_attacks(piece.pos, colour).squares() {
+            if self.colour(colour.opposite()).contains(to) {
+                targets.push(ResolvedMove::new(
+                    piece.piece_type,
+                    piece.pos,
+                    to,
+                    Capture::Yes,
+                ));
+            } else if self.en_passant == Some(to) {
+                targets.push(ResolvedMove {
+                    en_passant: true,
+                    ..ResolvedMove::new(piece.piece_type, piece.pos, to, Capture::Yes)
+                });
+            }
+        }
+
+        for mv in targets {
+            if mv.to.rank() != last_rank {
+                moves.push(mv);
+                continue;
+            }
+
+            for symbol in "QRBN".chars() {
+                moves.push(ResolvedMove {
+                    promotion: Promotion::Yes(PieceType::from_char_and_colour(symbol, colour)),
+                    ..mv
+                });
+            }
+        }
+    }
+
+    fn castling_moves(&self, king: Square, moves: &mut Vec<ResolvedMove>) {
+        let Some(king_type) = self.piece_at(king) else {
+            return;
+        };
+        let colour = king_type.get_colour();
+        let (rank, short, long) = match colour {
+            Colour::White => (
+                Rank::FIRST,
+                self.castling.white_short,
+                self.castling.white_long,
+            ),
+            Colour::Black => (
+                Rank::EIGHTH,
+                self.castling.black_short,
+                self.castling.black_long,
+            ),
+        };
+        let at = |file| Square::new(file, rank);
+
+        if king != at(File::E) || self.is_attacked(king, colour.opposite()) {
+            return;
+        }
+
+        let candidates = [
+            (
+                short,
+                Castle::Short(at(File::H)),
+                [File::F, File::G],
+                vec![File::F, File::G],
+            ),
+            (
+                long,
+                Castle::Long(at(File::A)),
+                [File::D, File::C],
+                vec![File::B, File::C, File::D],
+            ),
+        ];
+
+        for (allowed, castle, king_path, empty) in candidates {
+            let rook = match castle {
+                Castle::Short(rook) | Castle::Long(rook) => rook,
+                Castle::No => continue,
+            };
+            let king_to = at(king_path[1]);
+
+            let path_is_free = empty.iter().all(|f| self.piece_at(at(*f)).is_none());
+            let path_is_safe = king_path
+                .iter()
+                .all(|f| !self.is_attacked(at(*f), colour.opposite()));
+
+            if allowed
+                && self.piece_at(rook) == Some(PieceType::Rook(colour))
+                && path_is_free
+                && path_is_safe
+            {
+                moves.push(ResolvedMove {
+                    castle,
+                    ..ResolvedMove::new(king_type, king, king_to, Capture::No)
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{board::Board, movegen::ResolvedMove};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The standard perft positions with their node counts at depths 1 to 3.
+    const POSITIONS: [(&str, [u64; 3]); 6] = [
+        (crate::fen::START_POSITION, [20, 400, 8902]),
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            [48, 2039, 97862],
+        ),
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", [14, 191, 2812]),
+        (
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            [6, 264, 9467],
+        ),
+        (
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            [44, 1486, 62379],
+        ),
+        (
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            [46, 2079, 89890],
+        ),
+    ];
+
+    #[test]
+    fn test_standard_positions() {
+        for (fen, counts) in POSITIONS {
+            let board = Board::from_fen(fen).unwrap();
+
+            for (depth, expected) in (1..).zip(counts) {
+                assert_eq!(board.perft(depth), expected, "depth {depth} of {fen}");
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "slow in debug builds"]
+    fn test_standard_positions_depth_4() {
+        let counts = [197281, 4085603, 43238, 422333, 2103487, 3894594];
+
+        for ((fen, _), expected) in POSITIONS.into_iter().zip(counts) {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.perft(4), expected, "depth 4 of {fen}");
+        }
+    }
+
+    #[test]
+    fn test_divide() {
+        let board = Board::default();
+        let divide = board.perft_divide(2);
+
+        assert_eq!(divide.len(), 20);
+        assert!(divide.iter().all(|(_, nodes)| *nodes == 20));
+        assert_eq!(board.perft(0), 1);
+        assert!(board.perft_divide(0).is_empty());
+    }
+}
+
+impl Board {
+    /// The number of move paths `depth` plies long from this position.
+    pub fn perft(&self, depth: usize) -> u64 {
+        self.clone().count_paths(depth)
+    }
+
+    /// `perft` split by the first move, in the order of `legal_moves`.
+    pub fn perft_divide(&self, depth: usize) -> Vec<(ResolvedMove, u64)> {
+        if depth == 0 {
+            return vec![];
+        }
+
+        let mut board = self.clone();
+
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let undo = board.make_move(&mv);
+                let nodes = board.count_paths(depth - 1);
+                board.unmake_move(undo);
+                (mv, nodes)
+            })
+            .collect()
+    }
+
+    fn count_paths(&mut self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .iter()
+            .map(|mv| {
+                let undo = self.make_move(mv);
+                let nodes = self.count_paths(depth - 1);
+                self.unmake_move(undo);
+                nodes
+            })
+            .sum()
+    }
+}
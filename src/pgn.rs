@@ -0,0 +1,371 @@
+use std::fmt::Display;
+
+use crate::{
+    board::Board,
+    error::{Location, SjakkError},
+    turn::{Move, Turn},
+    types::Colour,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PieceType;
+
+    #[test]
+    fn test_tag_pairs() {
+        let game = PgnGame::parse(
+            "[Event \"F/S Return Match\"]\n[Site \"Belgrade, Serbia JUG\"]\n[Annotator \"a \\\"quoted\\\" name\"]\n\n1. e4 e5 *",
+        )
+        .unwrap();
+
+        assert_eq!(game.tags.len(), 3);
+        assert_eq!(game.tag("Event"), Some("F/S Return Match"));
+        assert_eq!(game.tag("Annotator"), Some("a \"quoted\" name"));
+        assert_eq!(game.tag("Round"), None);
+    }
+
+    #[test]
+    fn test_movetext() {
+        let game = PgnGame::parse(
+            "1. e4 {best by test} e5 2.Nf3 $1 Nc6 ; a comment\n3. Bb5 (3. Bc4 Bc5 (3... Nf6)) 3... a6!? 1/2-1/2",
+        )
+        .unwrap();
+
+        let expected = Turn::new_from_notation("Bb5 a6").unwrap();
+
+        assert_eq!(game.turns.len(), 3);
+        assert_eq!(game.turns[2], expected);
+        assert_eq!(game.result, GameResult::Draw);
+    }
+
+    #[test]
+    fn test_ends_on_white_move() {
+        let game = PgnGame::parse("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0").unwrap();
+        let last = game.turns.last().unwrap();
+
+        assert_eq!(game.turns.len(), 4);
+        assert!(last.value[0].is_some());
+        assert!(last.value[1].is_none());
+        assert_eq!(game.result, GameResult::WhiteWins);
+    }
+
+    #[test]
+    fn test_setup_position() {
+        let game = PgnGame::parse(
+            "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 20\"]\n\nKd7 21. e4 Kd6 *",
+        )
+        .unwrap();
+
+        assert_eq!(game.start.to_fen(), "4k3/8/8/8/8/8/4P3/4K3 b - - 0 20");
+        assert_eq!(game.turns.len(), 2);
+        assert_eq!(game.turns[0].value[0], None);
+        assert_eq!(
+            game.turns[0].value[1].unwrap().piece,
+            PieceType::King(Colour::Black)
+        );
+
+        let error =
+            PgnGame::parse("[White \"Fischer\"]\n[FEN \"4k3/8 w - -\"]\n\n1. e4 *").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: piece placement with 2 ranks instead of 8 '4k3/8'"
+        );
+    }
+
+    #[test]
+    fn test_set_start() {
+        let mut game = PgnGame::parse("Kd7 e4 Kd6").unwrap();
+        game.set_start(Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            game.turns,
+            PgnGame::parse("1... Kd7 2. e4 Kd6").unwrap().turns
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(PgnGame::parse("[Event \"unterminated").is_err());
+        assert!(PgnGame::parse("1. e4 {never closed").is_err());
+        assert!(PgnGame::parse("1. e4 e5 2. Nf3 (2. f4").is_err());
+        assert!(PgnGame::parse("1. e4 e5 )").is_err());
+    }
+
+    #[test]
+    fn test_error_location() {
+        let error = PgnGame::parse("[White \"Fischer\"]\n\n1. e4 e5\n2. Nf3 Xc6 *").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 4, column 8: not a valid chess move 'Xc6'"
+        );
+
+        let error = PgnGame::parse("1. e4 {never closed").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 7: unterminated comment '{'"
+        );
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    #[default]
+    Unknown,
+}
+
+impl GameResult {
+    pub fn from_token<S>(value: S) -> Option<Self>
+    where
+        S: AsRef<str>,
+    {
+        match value.as_ref() {
+            "1-0" => Some(Self::WhiteWins),
+            "0-1" => Some(Self::BlackWins),
+            "1/2-1/2" => Some(Self::Draw),
+            "*" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WhiteWins => write!(f, "1-0"),
+            Self::BlackWins => write!(f, "0-1"),
+            Self::Draw => write!(f, "1/2-1/2"),
+            Self::Unknown => write!(f, "*"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagPair {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<TagPair>,
+    /// The position before the first move, taken from the FEN tag when there is one.
+    pub start: Board,
+    pub turns: Vec<Turn>,
+    pub result: GameResult,
+}
+
+impl PgnGame {
+    /// Parses a single game in portable game notation.
+    ///
+    /// Comments, NAGs and variations are skipped, only the main line ends up in `turns`.
+    /// Move numbers are optional, so the old one-turn-per-line format is accepted as well.
+    /// A FEN tag sets the starting position, and with it who makes the first move.
+    pub fn parse<S>(value: S) -> Result<Self, SjakkError>
+    where
+        S: AsRef<str>,
+    {
+        Self::parse_inner(value.as_ref(), false)
+    }
+
+    /// Parses only the tag pairs of a game, stopping at the start of the movetext.
+    pub fn parse_tags<S>(value: S) -> Result<Vec<TagPair>, SjakkError>
+    where
+        S: AsRef<str>,
+    {
+        Ok(Self::parse_inner(value.as_ref(), true)?.tags)
+    }
+
+    fn parse_inner(value: &str, tags_only: bool) -> Result<Self, SjakkError> {
+        let mut game = Self::default();
+        let mut chars = value.char_indices().peekable();
+        let mut colour = Colour::White;
+        let mut variations: Vec<usize> = vec![];
+        let mut line_start = true;
+
+        while let Some((i, c)) = chars.next() {
+            let at_line_start = line_start;
+            let location = || Location::in_text(value, i);
+            line_start = c == '\n';
+
+            match c {
+                '%' if at_line_start => {
+                    chars.by_ref().find(|(_, c)| *c == '\n');
+                    line_start = true;
+                }
+                ';' => {
+                    chars.by_ref().find(|(_, c)| *c == '\n');
+                    line_start = true;
+                }
+                '{' => {
+                    chars.by_ref().find(|(_, c)| *c == '}').ok_or_else(|| {
+                        SjakkError::parse("{", "unterminated comment").at(location())
+                    })?;
+                }
+                '(' => variations.push(i),
+                ')' => {
+                    variations.pop().ok_or_else(|| {
+                        SjakkError::parse(")", "unexpected end of variation").at(location())
+                    })?;
+                }
+                '[' if variations.is_empty() => {
+                    let tag = Self::parse_tag(&mut chars).map_err(|e| e.at(location()))?;
+
+                    if tag.name == "FEN" && !tags_only {
+                        // Point at the tag, not at the column within the FEN string.
+                        game.start = Board::from_fen(&tag.value).map_err(|e| match e {
+                            SjakkError::Parse { token, reason, .. } => {
+                                SjakkError::parse(token, reason).at(location())
+                            }
+                            e => e,
+                        })?;
+                        colour = game.start.side_to_move;
+                    }
+                    game.tags.push(tag);
+                }
+                '$' => {
+                    while chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {}
+                }
+                c if c.is_whitespace() => (),
+                _ if tags_only && variations.is_empty() => return Ok(game),
+                c => {
+                    let mut token = String::from(c);
+                    while let Some((_, c)) = chars.next_if(|(_, c)| !Self::is_delimiter(*c)) {
+                        token.push(c);
+                    }
+
+                    if variations.is_empty() {
+                        game.push_token(&token, &mut colour)
+                            .map_err(|e| e.at(location()))?;
+                    }
+                }
+            }
+        }
+
+        if let Some(i) = variations.first() {
+            return Err(
+                SjakkError::parse("(", "unterminated variation").at(Location::in_text(value, *i))
+            );
+        }
+
+        Ok(game)
+    }
+
+    pub fn tag<S>(&self, name: S) -> Option<&str>
+    where
+        S: AsRef<str>,
+    {
+        self.tags
+            .iter()
+            .find(|t| t.name == name.as_ref())
+            .map(|t| t.value.as_str())
+    }
+
+    pub(crate) fn is_delimiter(c: char) -> bool {
+        c.is_whitespace() || "{}()[];$".contains(c)
+    }
+
+    fn parse_tag<I>(chars: &mut std::iter::Peekable<I>) -> Result<TagPair, SjakkError>
+    where
+        I: Iterator<Item = (usize, char)>,
+    {
+        let unterminated = || SjakkError::parse("[", "unterminated tag pair");
+        let mut next = || chars.next().map(|(_, c)| c).ok_or_else(unterminated);
+
+        let mut name = String::new();
+        loop {
+            match next()? {
+                '"' if !name.is_empty() => break,
+                c if c.is_whitespace() => (),
+                c if c.is_alphanumeric() || c == '_' => name.push(c),
+                c => return Err(SjakkError::parse(format!("{name}{c}"), "invalid tag name")),
+            }
+        }
+
+        let mut value = String::new();
+        loop {
+            match next()? {
+                '\\' => value.push(next()?),
+                '"' => break,
+                c => value.push(c),
+            }
+        }
+
+        chars
+            .by_ref()
+            .map(|(_, c)| c)
+            .find(|c| !c.is_whitespace())
+            .filter(|c| *c == ']')
+            .ok_or_else(unterminated)?;
+
+        Ok(TagPair { name, value })
+    }
+
+    fn push_token(&mut self, token: &str, colour: &mut Colour) -> Result<(), SjakkError> {
+        if let Some(result) = GameResult::from_token(token) {
+            self.result = result;
+            return Ok(());
+        }
+
+        let mut notation = token;
+
+        // Move numbers, either standalone or glued to the move as in "12.Nf3" or "12...Nf6".
+        if notation.starts_with(|c: char| c.is_ascii_digit()) && notation.contains('.') {
+            let digits = notation.trim_start_matches(|c: char| c.is_ascii_digit());
+            let rest = digits.trim_start_matches('.');
+
+            *colour = match digits.len() - rest.len() {
+                1 => Colour::White,
+                _ => Colour::Black,
+            };
+            notation = rest;
+        }
+
+        let notation = notation
+            .trim_end_matches(['!', '?'])
+            .trim_end_matches("e.p.")
+            .replace('0', "O");
+
+        if notation.is_empty() {
+            return Ok(());
+        }
+
+        self.push_move(Move::new_from_notation(&notation, *colour)?);
+        *colour = colour.opposite();
+
+        Ok(())
+    }
+
+    /// Starts the game from `start` instead, with the moves played alternately from its
+    /// side to move.
+    pub fn set_start(&mut self, start: Board) -> Result<(), SjakkError> {
+        let plies: Vec<Move> = self.turns.iter().flat_map(Turn::plies).collect();
+        let mut colour = start.side_to_move;
+
+        self.turns.clear();
+        for mv in plies {
+            self.push_move(Move::new_from_notation(mv.to_string(), colour)?);
+            colour = colour.opposite();
+        }
+        self.start = start;
+
+        Ok(())
+    }
+
+    /// Adds `mv` to the main line, starting a new turn when White moves.
+    pub fn push_move(&mut self, mv: Move) {
+        match (mv.piece.get_colour(), self.turns.last_mut()) {
+            (Colour::Black, Some(turn)) if turn.value[1].is_none() => turn.value[1] = Some(mv),
+            (Colour::White, _) => self.turns.push(Turn {
+                value: [Some(mv), None],
+            }),
+            (Colour::Black, _) => self.turns.push(Turn {
+                value: [None, Some(mv)],
+            }),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::fmt::Display;
+
+use crate::{
+    board::Board,
+    fen::START_POSITION,
+    pgn::{GameResult, PgnGame, TagPair},
+    turn::Move,
+    types::Colour,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(movetext: &str) -> Vec<MovetextElement> {
+        PgnGame::parse(movetext)
+            .unwrap()
+            .turns
+            .iter()
+            .flat_map(|t| t.plies())
+            .map(MovetextElement::Move)
+            .collect()
+    }
+
+    #[test]
+    fn test_seven_tag_roster() {
+        let pgn = PgnWriter::from_game(
+            &PgnGame::parse(
+                "[White \"Fischer, Robert J.\"]\n[ECO \"C95\"]\n[Annotator \"a \\\"b\\\"\"]\n1. e4 e5 1-0",
+            )
+            .unwrap(),
+        )
+        .to_string();
+
+        assert_eq!(
+            pgn,
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+             [White \"Fischer, Robert J.\"]\n[Black \"?\"]\n[Result \"1-0\"]\n\
+             [Annotator \"a \\\"b\\\"\"]\n[ECO \"C95\"]\n\n1. e4 e5 1-0\n"
+        );
+    }
+
+    #[test]
+    fn test_comments_and_variations() {
+        let mut writer = PgnWriter::new();
+        writer.movetext = moves("1. e4 e5 2. Nf3");
+        writer
+            .movetext
+            .push(MovetextElement::Comment("the main line".to_string()));
+        writer
+            .movetext
+            .push(MovetextElement::Variation(moves("2. f4 exf4")));
+        writer.movetext.extend(moves("2... Nc6"));
+        writer.movetext.insert(
+            2,
+            MovetextElement::Variation(vec![
+                MovetextElement::Comment("or".to_string()),
+                MovetextElement::Move(Move::new_from_notation("c5", 'b').unwrap()),
+            ]),
+        );
+
+        assert!(writer.to_string().ends_with(
+            "\n1. e4 e5 ({or} 1... c5) 2. Nf3 {the main line} (2. f4 exf4) 2... Nc6 *\n"
+        ));
+    }
+
+    #[test]
+    fn test_closing_brace_in_comment() {
+        let mut writer = PgnWriter::new();
+        writer.movetext = moves("1. e4");
+        writer
+            .movetext
+            .push(MovetextElement::Comment("see {the} note }".to_string()));
+        writer.movetext.extend(moves("1... e5"));
+        let pgn = writer.to_string();
+
+        assert!(pgn.ends_with("\n1. e4 {see {the note} 1... e5 *\n"));
+        assert_eq!(PgnGame::parse(&pgn).unwrap().turns.len(), 1);
+    }
+
+    #[test]
+    fn test_setup_position() {
+        let mut game = PgnGame::parse("Kd7 e4 Kd6").unwrap();
+        game.set_start(Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 20").unwrap())
+            .unwrap();
+        let pgn = PgnWriter::from_game(&game).to_string();
+
+        assert!(pgn.contains("\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 20\"]\n[SetUp \"1\"]\n"));
+        assert!(pgn.ends_with("\n20... Kd7 21. e4 Kd6 *\n"));
+
+        let reread = PgnGame::parse(&pgn).unwrap();
+        assert_eq!(reread.turns, game.turns);
+        assert_eq!(reread.start.to_fen(), game.start.to_fen());
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let game = PgnGame::parse(std::fs::read_to_string("bobby_game.txt").unwrap()).unwrap();
+        let pgn = PgnWriter::from_game(&game).to_string();
+        let movetext = pgn.split("\n\n").nth(1).unwrap();
+
+        assert!(movetext.lines().all(|l| l.len() <= 80));
+        assert!(movetext.lines().count() > 1);
+        assert!(movetext.ends_with("56. f6 1-0\n"));
+        assert_eq!(PgnGame::parse(&pgn).unwrap().turns, game.turns);
+    }
+}
+
+/// The longest a line of movetext may be.
+const LINE_WIDTH: usize = 80;
+
+/// Tags every exported game starts with, in this order.
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// One piece of movetext: a move, a comment, or a variation replacing the move before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MovetextElement {
+    Move(Move),
+    Comment(String),
+    Variation(Vec<MovetextElement>),
+}
+
+/// Writes a game in PGN export format.
+///
+/// The Seven Tag Roster always comes first, filled with "?" where a tag is missing,
+/// followed by any other tags by name. Movetext is wrapped at 80 columns.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PgnWriter {
+    pub tags: Vec<TagPair>,
+    pub movetext: Vec<MovetextElement>,
+    pub result: GameResult,
+}
+
+impl PgnWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_game(game: &PgnGame) -> Self {
+        let mut writer = Self {
+            tags: game.tags.clone(),
+            movetext: game
+                .turns
+                .iter()
+                .flat_map(|t| t.plies())
+                .map(MovetextElement::Move)
+                .collect(),
+            result: game.result,
+        };
+
+        let fen = game.start.to_fen();
+        if fen != START_POSITION {
+            writer.set_tag("SetUp", "1");
+            writer.set_tag("FEN", fen);
+        }
+
+        writer
+    }
+
+    /// Sets the tag `name`, replacing any value it had.
+    pub fn set_tag<N, V>(&mut self, name: N, value: V)
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        let name = name.into();
+        let value = value.into();
+
+        match self.tags.iter_mut().find(|t| t.name == name) {
+            Some(tag) => tag.value = value,
+            None => self.tags.push(TagPair { name, value }),
+        }
+    }
+
+    fn tag_value(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.value.as_str())
+    }
+
+    fn tag_pairs(&self) -> Vec<(String, String)> {
+        let result = self.result.to_string();
+        let mut pairs: Vec<(String, String)> = SEVEN_TAG_ROSTER
+            .iter()
+            .map(|(name, default)| {
+                let value = match *name {
+                    "Result" => result.as_str(),
+                    _ => self.tag_value(name).unwrap_or(default),
+                };
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+
+        let mut others: Vec<&TagPair> = self
+            .tags
+            .iter()
+            .filter(|t| SEVEN_TAG_ROSTER.iter().all(|(name, _)| *name != t.name))
+            .collect();
+        others.sort_by(|a, b| a.name.cmp(&b.name));
+
+        pairs.extend(others.iter().map(|t| (t.name.clone(), t.value.clone())));
+        pairs
+    }
+}
+
+/// Adds the tokens of `elements`, whose first move is numbered `number`, to `tokens`.
+fn push_tokens(tokens: &mut Vec<String>, elements: &[MovetextElement], mut number: usize) {
+    let mut needs_number = true;
+    let mut last_number = number;
+
+    for element in elements {
+        match element {
+            MovetextElement::Move(mv) => {
+                let colour = mv.piece.get_colour();
+
+                // The move number stays on the same line as its move.
+                tokens.push(match colour {
+                    Colour::White => format!("{number}. {mv}"),
+                    Colour::Black if needs_number => format!("{number}... {mv}"),
+                    Colour::Black => mv.to_string(),
+                });
+
+                last_number = number;
+                if colour == Colour::Black {
+                    number += 1;
+                }
+                needs_number = false;
+            }
+            MovetextElement::Comment(comment) => {
+                // A "}" would end the comment early, and PGN has no way to escape it.
+                let comment = comment.replace('}', "");
+                let words: Vec<&str> = comment.split_whitespace().collect();
+                let text = format!("{{{}}}", words.join(" "));
+
+                tokens.extend(text.split(' ').map(|word| word.to_string()));
+                needs_number = true;
+            }
+            MovetextElement::Variation(variation) => {
+                tokens.push("(".to_string());
+                push_tokens(tokens, variation, last_number);
+                tokens.push(")".to_string());
+                needs_number = true;
+            }
+        }
+    }
+}
+
+impl Display for PgnWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in self.tag_pairs() {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{name} \"{value}\"]")?;
+        }
+        writeln!(f)?;
+
+        let mut tokens = vec![];
+        let number = self
+            .tag_value("FEN")
+            .and_then(|fen| Board::from_fen(fen).ok())
+            .map_or(1, |board| board.fullmove_number as usize);
+        push_tokens(&mut tokens, &self.movetext, number);
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+        for token in &tokens {
+            // Variations hug their first and last token, as in "(2. f4 exf4)".
+            let glued = line.ends_with('(') || token == ")";
+            let width = line.len() + token.len() + usize::from(!glued);
+
+            if !line.is_empty() && width > LINE_WIDTH {
+                writeln!(f, "{line}")?;
+                line.clear();
+            } else if !line.is_empty() && !glued {
+                line.push(' ');
+            }
+            line.push_str(token);
+        }
+        writeln!(f, "{line}")
+    }
+}
@@ -0,0 +1,262 @@
+use std::fmt::Display;
+
+use crate::{
+    board::Board,
+    diagram::DiagramStyle,
+    error::SjakkError,
+    movegen::{ResolvedMove, UndoInfo},
+    turn::Move,
+    types::Colour,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pgn::PgnGame, turn::Turn};
+
+    fn replayer(movetext: &str) -> Replayer {
+        let game = PgnGame::parse(movetext).unwrap();
+        Replayer::new(game.start, game.turns.iter().flat_map(Turn::plies)).unwrap()
+    }
+
+    #[test]
+    fn test_stepping() {
+        let mut replayer = replayer("1. e4 e5 2. Nf3 Nc6 3. Bb5");
+
+        assert_eq!(replayer.len(), 5);
+        assert_eq!(replayer.ply(), 0);
+        assert_eq!(replayer.last_move(), None);
+        assert!(!replayer.back());
+
+        assert!(replayer.forward());
+        assert!(replayer.forward());
+        assert_eq!(replayer.last_move().unwrap().to_string(), "e5");
+        assert_eq!(
+            replayer.board().to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+
+        replayer.go_to(usize::MAX);
+        assert_eq!(replayer.ply(), 5);
+        assert!(!replayer.forward());
+
+        replayer.go_to(0);
+        assert_eq!(replayer.board().to_fen(), crate::fen::START_POSITION);
+    }
+
+    #[test]
+    fn test_move_list() {
+        let mut replayer = replayer("1. e4 e5 2. Nf3 Nc6 3. Bb5");
+        replayer.go_to(4);
+
+        assert_eq!(replayer.move_list(), "1. e4 e5 2. Nf3 [Nc6] 3. Bb5");
+        assert!(replayer.to_string().contains("Last move: 2... Nc6"));
+
+        replayer.go_to(0);
+        assert!(replayer.to_string().contains("Start position"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let turns = PgnGame::parse("1. e4 e5 2. Ke3").unwrap().turns;
+        let error =
+            Replayer::new(Board::default(), turns.iter().flat_map(Turn::plies)).unwrap_err();
+
+        assert_eq!(error.to_string(), "ply 3: no piece can play Ke3");
+    }
+
+    #[test]
+    fn test_setup_position() {
+        let mut replayer =
+            replayer("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 20\"]\n\n20... Kd7 21. e4 Kd6 *");
+
+        assert_eq!(
+            replayer.board().to_fen(),
+            "4k3/8/8/8/8/8/4P3/4K3 b - - 0 20"
+        );
+        assert!(replayer.to_string().contains("Start position"));
+
+        replayer.go_to(1);
+        assert!(replayer.to_string().contains("Last move: 20... Kd7"));
+        assert_eq!(replayer.move_list(), "20... [Kd7] 21. e4 Kd6");
+
+        replayer.go_to(0);
+        assert_eq!(
+            replayer.board().to_fen(),
+            "4k3/8/8/8/8/8/4P3/4K3 b - - 0 20"
+        );
+    }
+}
+
+/// The longest a line of the move list may be.
+const LINE_WIDTH: usize = 80;
+
+/// Steps back and forth through a game from its starting position, making and
+/// unmaking moves on a single board.
+#[derive(Debug, Clone)]
+pub struct Replayer {
+    board: Board,
+    /// The ply the game starts at, counted from 0 at White's first move of the game.
+    first_ply: usize,
+    moves: Vec<ResolvedMove>,
+    san: Vec<Move>,
+    undo: Vec<UndoInfo>,
+    style: DiagramStyle,
+}
+
+impl Replayer {
+    /// Resolves every move of the game played from `start` against the legal moves,
+    /// failing at the first one that can't be played.
+    pub fn new<I>(start: Board, plies: I) -> Result<Self, SjakkError>
+    where
+        I: IntoIterator<Item = Move>,
+    {
+        let mut board = start.clone();
+        let mut moves = vec![];
+        let mut san = vec![];
+
+        for (i, mv) in plies.into_iter().enumerate() {
+            let resolved = board.resolve(&mv).map_err(|e| e.at_ply(i + 1))?;
+
+            san.push(board.san(&resolved));
+            moves.push(resolved);
+            board.make_move(&resolved);
+        }
+
+        let first_ply = 2 * (start.fullmove_number.saturating_sub(1) as usize)
+            + usize::from(start.side_to_move == Colour::Black);
+
+        Ok(Self {
+            board: start,
+            first_ply,
+            moves,
+            san,
+            undo: vec![],
+            style: DiagramStyle::default(),
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Sets how the board is drawn.
+    pub fn set_style(&mut self, style: DiagramStyle) {
+        self.style = style;
+    }
+
+    /// How many plies have been played to reach the current position.
+    pub fn ply(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// The number of plies in the game.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// The move that led to the current position, in SAN.
+    pub fn last_move(&self) -> Option<Move> {
+        self.ply().checked_sub(1).map(|i| self.san[i])
+    }
+
+    /// Plays the next move. Returns `false` at the end of the game.
+    pub fn forward(&mut self) -> bool {
+        match self.moves.get(self.ply()) {
+            Some(mv) => {
+                self.undo.push(self.board.make_move(mv));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Takes back the last move. Returns `false` at the start of the game.
+    pub fn back(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(undo) => {
+                self.board.unmake_move(undo);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Steps to the position after `ply` plies, or to the end when the game is shorter.
+    pub fn go_to(&mut self, ply: usize) {
+        while self.ply() > ply && self.back() {}
+        while self.ply() < ply && self.forward() {}
+    }
+
+    /// The moves of the game with numbers, wrapped at 80 columns. The move that led to
+    /// the current position is put in brackets.
+    pub fn move_list(&self) -> String {
+        let mut lines: Vec<String> = vec![];
+        let mut line = String::new();
+
+        for (i, mv) in self.san.iter().enumerate() {
+            let mv = if i + 1 == self.ply() {
+                format!("[{mv}]")
+            } else {
+                mv.to_string()
+            };
+            let (number, colour) = self.numbered(i);
+            let token = match colour {
+                Colour::White => format!("{number}. {mv}"),
+                Colour::Black if i == 0 => format!("{number}... {mv}"),
+                Colour::Black => mv,
+            };
+
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                lines.push(std::mem::take(&mut line));
+            } else if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        lines.push(line);
+
+        lines.join("\n")
+    }
+
+    /// The move number of the `i`th ply played, counted from 0, and who plays it.
+    fn numbered(&self, i: usize) -> (usize, Colour) {
+        let ply = self.first_ply + i;
+        let colour = if ply.is_multiple_of(2) {
+            Colour::White
+        } else {
+            Colour::Black
+        };
+
+        (ply / 2 + 1, colour)
+    }
+}
+
+impl Display for Replayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.board.diagram(self.style))?;
+
+        match self.last_move() {
+            Some(mv) => {
+                let (number, colour) = self.numbered(self.ply() - 1);
+                let dots = match colour {
+                    Colour::White => ".",
+                    Colour::Black => "...",
+                };
+                writeln!(f, "Last move: {number}{dots} {mv}")?;
+            }
+            None => writeln!(f, "Start position")?,
+        }
+
+        if let Some(outcome) = self.board.outcome() {
+            writeln!(f, "{outcome}")?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{}", self.move_list())
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    board::Board,
+    error::SjakkError,
+    movegen::ResolvedMove,
+    square::{File, Rank, Square},
+    turn::Move,
+    types::{Castle, PieceType, Promotion},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::sq;
+    use crate::types::Colour;
+
+    fn san(fen: &str, from: &str, to: &str) -> String {
+        let board = Board::from_fen(fen).unwrap();
+        let mv = board.find_move(sq(from), sq(to), None).unwrap();
+
+        board.san(&mv).to_string()
+    }
+
+    #[test]
+    fn test_plain_moves() {
+        let start = crate::fen::START_POSITION;
+
+        assert_eq!(san(start, "e2", "e4"), "e4");
+        assert_eq!(san(start, "g1", "f3"), "Nf3");
+        assert_eq!(
+            san(
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                "e4",
+                "d5"
+            ),
+            "exd5"
+        );
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        // Knights on b1 and f3 can both reach d2.
+        let fen = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+        assert_eq!(san(fen, "b1", "d2"), "Nbd2");
+
+        // Rooks on e1 and e3, same file.
+        let fen = "7k/8/8/8/8/4R3/8/K3R3 w - - 0 1";
+        assert_eq!(san(fen, "e1", "e2"), "R1e2");
+
+        // Queens on e4, h4 and h1 can all reach e1.
+        let fen = "1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1";
+        assert_eq!(san(fen, "h4", "e1"), "Qh4e1");
+        assert_eq!(san(fen, "e4", "e1"), "Qee1");
+        assert_eq!(san(fen, "h1", "e1"), "Q1e1");
+
+        // The knight on g1 is pinned, so Nd2 needs no disambiguation.
+        let fen = "4k3/8/8/8/8/8/8/1N2K1Nr w - - 0 1";
+        assert_eq!(san(fen, "b1", "d2"), "Nd2");
+    }
+
+    #[test]
+    fn test_special_moves() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(san(fen, "e1", "g1"), "O-O");
+        assert_eq!(san(fen, "e1", "c1"), "O-O-O");
+
+        let board = Board::from_fen("3qk3/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let knight = PieceType::Knight(Colour::White);
+        let mv = board.find_move(sq("e7"), sq("d8"), Some(knight)).unwrap();
+        assert_eq!(board.san(&mv).to_string(), "exd8=N");
+
+        let fools_mate = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2";
+        assert_eq!(san(fools_mate, "d8", "h4"), "Qh4#");
+    }
+
+    #[test]
+    fn test_resolve() {
+        let board = Board::from_fen("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1").unwrap();
+        let sloppy = Move::new_from_notation("Nbd2+", 'w').unwrap();
+
+        let resolved = board.resolve(&sloppy).unwrap();
+        assert_eq!(resolved.from, sq("b1"));
+        assert_eq!(board.san(&resolved).to_string(), "Nbd2");
+
+        let ambiguous = Move::new_from_notation("Nd2", 'w').unwrap();
+        assert_eq!(
+            board.resolve(&ambiguous).unwrap_err().to_string(),
+            "Nd2 is ambiguous, it can be played from b1, f3"
+        );
+        assert!(board
+            .resolve(&Move::new_from_notation("Nd3", 'w').unwrap())
+            .is_err());
+    }
+}
+
+impl Board {
+    /// The legal move from `from` to `to`, promoting to `promotion` when a pawn reaches the last rank.
+    pub fn find_move(
+        &self,
+        from: Square,
+        to: Square,
+        promotion: Option<PieceType>,
+    ) -> Option<ResolvedMove> {
+        let promotion = match promotion {
+            Some(piece) => Promotion::Yes(piece),
+            None => Promotion::No,
+        };
+
+        self.legal_moves()
+            .into_iter()
+            .find(|m| m.from == from && m.to == to && m.promotion == promotion)
+    }
+
+    /// Matches a parsed move against the legal moves in this position.
+    ///
+    /// The check annotation of `mv` is ignored, so moves with a wrong or missing `+` still resolve.
+    pub fn resolve(&self, mv: &Move) -> Result<ResolvedMove, SjakkError> {
+        let candidates: Vec<ResolvedMove> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|m| match mv.castle {
+                Castle::No => {
+                    m.castle == Castle::No
+                        && m.piece == mv.piece
+                        && Some(m.to) == mv.to
+                        && m.promotion == mv.promotion
+                        && mv.from.0.is_none_or(|file| file == m.from.file())
+                        && mv.from.1.is_none_or(|rank| rank == m.from.rank())
+                }
+                castle => m.castle == castle,
+            })
+            .collect();
+
+        match candidates[..] {
+            [resolved] => Ok(resolved),
+            [] => Err(SjakkError::NoSuchPiece {
+                notation: mv.to_string(),
+                ply: None,
+            }),
+            _ => {
+                let mut squares: Vec<Square> = candidates.iter().map(|m| m.from).collect();
+                squares.sort_unstable();
+                squares.dedup();
+
+                Err(SjakkError::AmbiguousMove {
+                    notation: mv.to_string(),
+                    candidates: squares,
+                    ply: None,
+                })
+            }
+        }
+    }
+
+    /// The move in minimal Standard Algebraic Notation, with the check annotation taken from
+    /// the position it leads to. `mv` must be legal in this position.
+    pub fn san(&self, mv: &ResolvedMove) -> Move {
+        Move {
+            piece: mv.piece,
+            castle: mv.castle,
+            capture: mv.capture,
+            promotion: mv.promotion,
+            check: self.with_move(mv).check_annotation(),
+            from: self.disambiguation(mv),
+            to: Some(mv.to),
+        }
+    }
+
+    /// As much of the starting square as SAN needs to tell `mv` apart from other legal moves.
+    fn disambiguation(&self, mv: &ResolvedMove) -> (Option<File>, Option<Rank>) {
+        let (file, rank) = (mv.from.file(), mv.from.rank());
+
+        match (mv.piece, mv.castle) {
+            (_, Castle::Short(_) | Castle::Long(_)) => return (None, None),
+            (PieceType::Pawn(_), _) if file != mv.to.file() => return (Some(file), None),
+            (PieceType::Pawn(_), _) => return (None, None),
+            _ => (),
+        }
+
+        let rivals: Vec<Square> = self
+            .legal_moves()
+            .iter()
+            .filter(|m| m.piece == mv.piece && m.to == mv.to && m.from != mv.from)
+            .map(|m| m.from)
+            .collect();
+
+        if rivals.is_empty() {
+            (None, None)
+        } else if rivals.iter().all(|r| r.file() != file) {
+            (Some(file), None)
+        } else if rivals.iter().all(|r| r.rank() != rank) {
+            (None, Some(rank))
+        } else {
+            (Some(file), Some(rank))
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::error::SjakkError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let e4: Square = "e4".parse().unwrap();
+
+        assert_eq!(e4.file(), File::E);
+        assert_eq!(e4.rank(), Rank::FOURTH);
+        assert_eq!(e4.to_string(), "e4");
+        assert_eq!(Square::new(File::H, Rank::EIGHTH).to_string(), "h8");
+
+        for invalid in ["", "e", "e9", "i4", "e44", "4e"] {
+            assert!(invalid.parse::<Square>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_offset() {
+        let a1: Square = "a1".parse().unwrap();
+
+        assert_eq!(a1.offset(1, 2), Some("b3".parse().unwrap()));
+        assert_eq!(a1.offset(-1, 0), None);
+        assert_eq!(a1.offset(0, 8), None);
+        assert_eq!("h8".parse::<Square>().unwrap().offset(-7, -7), Some(a1));
+    }
+
+    #[test]
+    fn test_index() {
+        assert_eq!(Square::all().count(), 64);
+        assert!(Square::all().enumerate().all(|(i, s)| s.index() == i));
+        assert_eq!("b1".parse::<Square>().unwrap().index(), 1);
+        assert_eq!("a2".parse::<Square>().unwrap().index(), 8);
+    }
+}
+
+/// The square named `name`, such as "e4", for writing tests briefly.
+#[cfg(test)]
+pub(crate) fn sq(name: &str) -> Square {
+    name.parse().unwrap()
+}
+
+/// A column of the board, from the a-file to the h-file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct File(u8);
+
+/// A row of the board, from the first rank to the eighth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rank(u8);
+
+/// One of the 64 squares, numbered a1 = 0, b1 = 1, ..., h8 = 63.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square(u8);
+
+impl File {
+    pub const A: Self = Self(0);
+    pub const B: Self = Self(1);
+    pub const C: Self = Self(2);
+    pub const D: Self = Self(3);
+    pub const E: Self = Self(4);
+    pub const F: Self = Self(5);
+    pub const G: Self = Self(6);
+    pub const H: Self = Self(7);
+
+    /// The file with the given index, a = 0.
+    pub fn new(index: i8) -> Option<Self> {
+        (0..8).contains(&index).then_some(Self(index as u8))
+    }
+
+    pub fn from_char(symbol: char) -> Option<Self> {
+        ('a'..='h')
+            .contains(&symbol)
+            .then(|| Self(symbol as u8 - b'a'))
+    }
+
+    pub fn index(self) -> i8 {
+        self.0 as i8
+    }
+}
+
+impl Rank {
+    pub const FIRST: Self = Self(0);
+    pub const SECOND: Self = Self(1);
+    pub const THIRD: Self = Self(2);
+    pub const FOURTH: Self = Self(3);
+    pub const FIFTH: Self = Self(4);
+    pub const SIXTH: Self = Self(5);
+    pub const SEVENTH: Self = Self(6);
+    pub const EIGHTH: Self = Self(7);
+
+    /// The rank with the given index, the first rank = 0.
+    pub fn new(index: i8) -> Option<Self> {
+        (0..8).contains(&index).then_some(Self(index as u8))
+    }
+
+    pub fn from_char(symbol: char) -> Option<Self> {
+        ('1'..='8')
+            .contains(&symbol)
+            .then(|| Self(symbol as u8 - b'1'))
+    }
+
+    pub fn index(self) -> i8 {
+        self.0 as i8
+    }
+}
+
+impl Square {
+    pub fn new(file: File, rank: Rank) -> Self {
+        Self(rank.0 * 8 + file.0)
+    }
+
+    /// The square with the given file and rank indices, if both are on the board.
+    pub fn from_coords(file: i8, rank: i8) -> Option<Self> {
+        Some(Self::new(File::new(file)?, Rank::new(rank)?))
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        (index < 64).then_some(Self(index as u8))
+    }
+
+    /// Every square, from a1 to h8.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..64).map(Self)
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn file(self) -> File {
+        File(self.0 % 8)
+    }
+
+    pub fn rank(self) -> Rank {
+        Rank(self.0 / 8)
+    }
+
+    /// The square `files` to the right and `ranks` up from this one, or `None` off the board.
+    pub fn offset(self, files: i8, ranks: i8) -> Option<Self> {
+        Self::from_coords(self.file().index() + files, self.rank().index() + ranks)
+    }
+}
+
+impl Display for File {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", (b'a' + self.0) as char)
+    }
+}
+
+impl Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0 + 1)
+    }
+}
+
+impl Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
+impl FromStr for Square {
+    type Err = SjakkError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut chars = value.chars();
+
+        match (
+            chars.next().and_then(File::from_char),
+            chars.next().and_then(Rank::from_char),
+            chars.next(),
+        ) {
+            (Some(file), Some(rank), None) => Ok(Self::new(file, rank)),
+            _ => Err(SjakkError::parse(value, "not a square")),
+        }
+    }
+}
@@ -1,14 +1,117 @@
-use std::io::{Error, ErrorKind};
+use std::fmt::Display;
 
-use crate::types::{Capture, Castle, Check, Colour, PieceType, Promotion};
+use crate::{
+    error::SjakkError,
+    square::{File, Rank, Square},
+    types::{Capture, Castle, Check, Colour, PieceType, Promotion},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::sq;
+
+    #[test]
+    fn test_single_move_turn() {
+        let turn = Turn::new_from_notation("f6").unwrap();
+
+        assert_eq!(
+            turn.value[0],
+            Some(Move::new_from_notation("f6", 'w').unwrap())
+        );
+        assert_eq!(turn.value[1], None);
+        assert_eq!(turn.plies().count(), 1);
+        assert!(Turn::new_from_notation(" ").is_err());
+    }
+
+    #[test]
+    fn test_promotion() {
+        let queen = Promotion::Yes(PieceType::Queen(Colour::White));
+
+        for notation in ["e8=Q", "e8Q"] {
+            let mv = Move::new_from_notation(notation, 'w').unwrap();
+            assert_eq!(mv.piece, PieceType::Pawn(Colour::White));
+            assert_eq!(mv.promotion, queen);
+            assert_eq!(mv.from, (None, None));
+            assert_eq!(mv.to, Some(sq("e8")));
+        }
+
+        for notation in ["exd8=Q+", "exd8Q+"] {
+            let mv = Move::new_from_notation(notation, 'w').unwrap();
+            assert_eq!(mv.promotion, queen);
+            assert_eq!(mv.from, (Some(File::E), None));
+            assert_eq!(mv.to, Some(sq("d8")));
+            assert_eq!(mv.check, Check::Check);
+        }
+
+        let mv = Move::new_from_notation("gxh1N", 'b').unwrap();
+        assert_eq!(
+            mv.promotion,
+            Promotion::Yes(PieceType::Knight(Colour::Black))
+        );
+        assert_eq!(mv.to_string(), "gxh1=N");
+
+        assert_eq!(
+            Move::new_from_notation("Qe4", 'w').unwrap().promotion,
+            Promotion::No
+        );
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        for (notation, from, to) in [
+            ("Nbd2", (Some(File::B), None), "d2"),
+            ("R1e2", (None, Some(Rank::FIRST)), "e2"),
+            ("N5xf3", (None, Some(Rank::FIFTH)), "f3"),
+            ("Qh4e1", (Some(File::H), Some(Rank::FOURTH)), "e1"),
+            ("Qh4xe1#", (Some(File::H), Some(Rank::FOURTH)), "e1"),
+            ("Kd2", (None, None), "d2"),
+        ] {
+            let mv = Move::new_from_notation(notation, 'w').unwrap();
+            assert_eq!(mv.from, from);
+            assert_eq!(mv.to, Some(sq(to)));
+            assert_eq!(mv.to_string(), notation);
+        }
+
+        assert!(Move::new_from_notation("Raab1", 'w').is_err());
+        assert!(Move::new_from_notation("N0f3", 'w').is_err());
+    }
+
+    #[test]
+    fn test_invalid_notation() {
+        assert!(Move::new_from_notation("Xe4", 'w').is_err());
+        assert!(Move::new_from_notation("Nz9", 'w').is_err());
+        assert!(Move::new_from_notation("", 'w').is_err());
+        assert!(Move::new_from_notation("e4", 'x').is_err());
+        assert!(Turn::new_from_notation("e4 Qh9").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        for notation in [
+            "e4 Nf6",
+            "Nbd2 exd4",
+            "O-O O-O-O",
+            "O-O+ O-O-O#",
+            "Qxf7# Kxf7",
+            "Rae1+ Bxb5",
+        ] {
+            assert_eq!(
+                Turn::new_from_notation(notation).unwrap().to_string(),
+                notation
+            );
+        }
+    }
+}
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Turn {
-    pub value: [Move; 2],
+    pub value: [Option<Move>; 2],
 }
 
 impl Turn {
-    pub fn new_from_notation<S>(value: S) -> Result<Self, Error>
+    /// Parses one line of the form "e4 e5". The black move may be missing on the last turn.
+    pub fn new_from_notation<S>(value: S) -> Result<Self, SjakkError>
     where
         S: AsRef<str>,
     {
@@ -16,18 +119,34 @@ impl Turn {
 
         let moves_from_str: Vec<Move> = value
             .as_ref()
-            .split(" ")
+            .split_whitespace()
             .zip(COLOURS)
             .map(|(s, c)| Move::new_from_notation(s, c))
-            .collect::<Result<Vec<Move>, Error>>()?;
+            .collect::<Result<Vec<Move>, SjakkError>>()?;
+
+        if moves_from_str.is_empty() {
+            return Err(SjakkError::parse(value.as_ref(), "no moves in turn"));
+        }
 
         Ok(Self {
             value: [
-                *moves_from_str.get(0).unwrap(),
-                *moves_from_str.get(1).unwrap(),
+                moves_from_str.first().copied(),
+                moves_from_str.get(1).copied(),
             ],
         })
     }
+
+    /// The moves of this turn in the order they were played.
+    pub fn plies(&self) -> impl Iterator<Item = Move> {
+        self.value.into_iter().flatten()
+    }
+}
+
+impl Display for Turn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let moves: Vec<String> = self.plies().map(|mv| mv.to_string()).collect();
+        write!(f, "{}", moves.join(" "))
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -37,25 +156,64 @@ pub struct Move {
     pub capture: Capture,
     pub promotion: Promotion,
     pub check: Check,
-    pub from: Option<usize>,
-    pub to: Option<[i8; 2]>,
+    /// The file and rank the piece moves from, as far as the notation tells.
+    pub from: (Option<File>, Option<Rank>),
+    pub to: Option<Square>,
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.castle {
+            Castle::Short(_) => write!(f, "O-O")?,
+            Castle::Long(_) => write!(f, "O-O-O")?,
+            Castle::No => {
+                if !matches!(self.piece, PieceType::Pawn(_)) {
+                    write!(f, "{:?}", self.piece)?;
+                }
+                if let Some(file) = self.from.0 {
+                    write!(f, "{file}")?;
+                }
+                if let Some(rank) = self.from.1 {
+                    write!(f, "{rank}")?;
+                }
+                if self.capture == Capture::Yes {
+                    write!(f, "x")?;
+                }
+                if let Some(to) = self.to {
+                    write!(f, "{to}")?;
+                }
+                if let Promotion::Yes(piece) = self.promotion {
+                    write!(f, "={piece:?}")?;
+                }
+            }
+        }
+
+        match self.check {
+            Check::No => Ok(()),
+            Check::Check => write!(f, "+"),
+            Check::Mate => write!(f, "#"),
+        }
+    }
 }
 
 impl Move {
-    pub fn new_from_notation<S, C>(notation: S, colour: C) -> Result<Self, Error>
+    pub fn new_from_notation<S, C>(notation: S, colour: C) -> Result<Self, SjakkError>
     where
         S: AsRef<str>,
-        C: Into<Colour> + Copy,
+        C: TryInto<Colour>,
+        SjakkError: From<C::Error>,
     {
+        let colour: Colour = colour.try_into()?;
         let piece: PieceType = PieceType::from_notation(&notation, colour)?;
         let castle: Castle = Castle::from_notation(&notation, colour);
         let capture: Capture = Capture::from_notation(&notation);
         let promotion: Promotion = Promotion::from_notation(&notation, colour);
         let check: Check = Check::from_notation(&notation);
-        let from: Option<usize> = None;
-        let to: Option<[i8; 2]> = None;
+        let notation = promotion.strip_notation(&notation);
+        let from: (Option<File>, Option<Rank>) = (None, None);
+        let to: Option<Square> = None;
 
-        Ok(Self {
+        Self {
             piece,
             castle,
             capture,
@@ -64,92 +222,77 @@ impl Move {
             from,
             to,
         }
-        .remove_ambiguity(&notation)?
-        .set_destination(&notation))
+        .set_destination(&notation)?
+        .remove_ambiguity(&notation)
     }
 
-    fn remove_ambiguity<S>(mut self, notation: S) -> Result<Self, Error>
-    where
-        S: AsRef<str>,
-    {
-        let notation = notation.as_ref();
-        let length = if self.check.is_check_or_mate() { 3 } else { 2 };
-
-        self.from = match self.piece {
-            PieceType::Pawn(_) => {
-                if notation.len() > length {
-                    "abcdefgh".find(
-                        notation
-                            .chars()
-                            .nth(0)
-                            .ok_or(Error::new(ErrorKind::InvalidInput, "notation is too short"))?,
-                    )
-                } else {
-                    None
-                }
-            }
-            PieceType::King(_) | PieceType::Queen(_) => None,
-            _ => {
-                if notation.len() > length + 1 {
-                    "abcdefgh".find(
-                        notation
-                            .chars()
-                            .nth(1)
-                            .ok_or(Error::new(ErrorKind::InvalidInput, "notation is too short"))?,
-                    )
-                } else {
-                    None
-                }
-            }
+    /// The squares of the move, e.g. "e1" for "Qxe1+" or "h4e1" for "Qh4xe1".
+    fn squares(&self, notation: &str) -> String {
+        let skip = match self.piece {
+            PieceType::Pawn(_) => 0,
+            _ => 1,
         };
-        Ok(self)
+
+        notation
+            .trim_end_matches(['+', '#'])
+            .chars()
+            .skip(skip)
+            .filter(|c| *c != 'x')
+            .collect()
     }
 
-    fn set_destination<S>(mut self, notation: S) -> Self
+    /// Reads the file, rank or square the piece moves from, when the notation gives one.
+    fn remove_ambiguity<S>(mut self, notation: S) -> Result<Self, SjakkError>
     where
         S: AsRef<str>,
     {
-        let offset: usize = self.get_offset();
-
-        self.to = if let PieceType::King(_) = self.piece {
-            match self.castle {
-                Castle::No => Move::format_destination(notation, offset),
-                Castle::Short(pos) => Some([6, pos[1]]),
-                Castle::Long(pos) => Some([2, pos[1]]),
-            }
-        } else {
-            Move::format_destination(notation, offset)
-        };
+        if self.castle != Castle::No {
+            return Ok(self);
+        }
 
-        self
-    }
+        let squares = self.squares(notation.as_ref());
+        let invalid =
+            || SjakkError::parse(notation.as_ref(), "move with an invalid disambiguation");
 
-    fn get_offset(&self) -> usize {
-        let mut offset = match self.capture {
-            Capture::Yes => match self.from {
-                Some(_) => 3,
-                None => 2,
-            },
-            Capture::No => match self.from {
-                Some(_) => 2,
-                None => 1,
-            },
-        };
+        for c in squares
+            .chars()
+            .take(squares.chars().count().saturating_sub(2))
+        {
+            let repeated = match (File::from_char(c), Rank::from_char(c)) {
+                (Some(file), _) => self.from.0.replace(file).is_some(),
+                (_, Some(rank)) => self.from.1.replace(rank).is_some(),
+                _ => return Err(invalid()),
+            };
 
-        if let PieceType::Pawn(_) = self.piece {
-            offset -= 1;
+            if repeated {
+                return Err(invalid());
+            }
         }
 
-        offset
+        Ok(self)
     }
 
-    fn format_destination<S>(notation: S, offset: usize) -> Option<[i8; 2]>
+    fn set_destination<S>(mut self, notation: S) -> Result<Self, SjakkError>
     where
         S: AsRef<str>,
     {
-        Some([
-            "abcdefgh".find(notation.as_ref().chars().nth(offset)?)? as i8,
-            notation.as_ref().chars().nth(offset + 1)?.to_digit(10)? as i8 - 1,
-        ])
+        self.to = match self.castle {
+            Castle::No => {
+                let squares = self.squares(notation.as_ref());
+                let split = squares.char_indices().rev().nth(1).map_or(0, |(i, _)| i);
+
+                squares[split..].parse().ok()
+            }
+            Castle::Short(rook) => Some(Square::new(File::G, rook.rank())),
+            Castle::Long(rook) => Some(Square::new(File::C, rook.rank())),
+        };
+
+        match self.to {
+            Some(_) => Ok(self),
+            None => Err(SjakkError::parse(
+                notation.as_ref(),
+                "move without a destination square",
+            )),
+        }
     }
 }
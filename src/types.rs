@@ -1,6 +1,8 @@
-use std::{
-    fmt::{Debug, Display},
-    io::{Error, ErrorKind},
+use std::fmt::{Debug, Display};
+
+use crate::{
+    error::SjakkError,
+    square::{File, Rank, Square},
 };
 
 const WHITE_PIECES: &str = "♙♔♕♗♘♖";
@@ -62,8 +64,7 @@ impl From<&PieceType> for String {
 }
 
 impl PieceType {
-    pub fn from_char(symbol: char) -> Result<Self, Error>
-    {
+    pub fn from_char(symbol: char) -> Result<Self, SjakkError> {
         match symbol {
             '♙' => Ok(PieceType::Pawn(Colour::White)),
             '♟' => Ok(PieceType::Pawn(Colour::Black)),
@@ -77,7 +78,10 @@ impl PieceType {
             '♞' => Ok(PieceType::Knight(Colour::Black)),
             '♖' => Ok(PieceType::Rook(Colour::White)),
             '♜' => Ok(PieceType::Rook(Colour::Black)),
-            _ => Err(Error::new(ErrorKind::InvalidInput, "not a valid identifier for a chess piece.")),
+            _ => Err(SjakkError::parse(
+                symbol,
+                "not a valid identifier for a chess piece",
+            )),
         }
     }
 
@@ -95,42 +99,21 @@ impl PieceType {
         }
     }
 
-    pub fn from_notation<S, C>(value: S, colour: C) -> Result<Self, Error>
+    pub fn from_notation<S, C>(value: S, colour: C) -> Result<Self, SjakkError>
     where
         S: AsRef<str>,
-        C: Into<Colour>
+        C: Into<Colour>,
     {
-        let mut piece: Option<PieceType> = None;
         let colour = colour.into();
 
-        for symbol in "KQBNR".chars() {
-            if let Some(pos) = value
-                .as_ref()
-                .find(|s: char| s.is_uppercase() && s == symbol)
-            {
-                piece = match pos {
-                    0 => Some(PieceType::from_char_and_colour(symbol, colour)),
-                    3 => Some(PieceType::Pawn(colour)),
-                    _ => {
-                        return Err(Error::new(
-                            ErrorKind::InvalidInput,
-                            "not a valid chess move",
-                        ))
-                    }
-                }
+        match value.as_ref().chars().next() {
+            Some('O') => Ok(PieceType::King(colour)),
+            Some(symbol) if "KQBNR".contains(symbol) => {
+                Ok(PieceType::from_char_and_colour(symbol, colour))
             }
+            Some(symbol) if "abcdefghx".contains(symbol) => Ok(PieceType::Pawn(colour)),
+            _ => Err(SjakkError::parse(value.as_ref(), "not a valid chess move")),
         }
-
-        if piece.is_none() {
-            piece = match value.as_ref().find('O') {
-                Some(_) => Some(PieceType::King(colour)),
-                None => Some(PieceType::Pawn(colour)),
-            };
-        }
-        piece.ok_or(Error::new(
-            ErrorKind::InvalidInput,
-            "not a valid chess move",
-        ))
     }
 
     pub fn get_colour(&self) -> Colour {
@@ -152,16 +135,36 @@ pub enum Colour {
     Black,
 }
 
-impl From<char> for Colour {
-    fn from(value: char) -> Self {
+impl Colour {
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+
+    /// 0 for white and 1 for black, for tables indexed by colour.
+    pub fn index(self) -> usize {
+        match self {
+            Self::White => 0,
+            Self::Black => 1,
+        }
+    }
+}
+
+impl TryFrom<char> for Colour {
+    type Error = SjakkError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            'w' | 'W' | '♙' | '♔' | '♕' | '♗' | '♘' | '♖' => Self::White,
-            'b' | 'B' | '♟' | '♚' | '♛' | '♝' | '♞' | '♜' => Self::Black,
-            _ => panic!(),
+            'w' | 'W' | '♙' | '♔' | '♕' | '♗' | '♘' | '♖' => Ok(Self::White),
+            'b' | 'B' | '♟' | '♚' | '♛' | '♝' | '♞' | '♜' => Ok(Self::Black),
+            _ => Err(SjakkError::parse(value, "not a colour")),
         }
     }
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Check {
     #[default]
@@ -185,10 +188,7 @@ impl Check {
     }
 
     pub fn is_check_or_mate(self) -> bool {
-        match self {
-            Check::No => false,
-            _ => true,
-        }
+        !matches!(self, Check::No)
     }
 }
 
@@ -196,8 +196,9 @@ impl Check {
 pub enum Castle {
     #[default]
     No,
-    Short([i8; 2]),
-    Long([i8; 2]),
+    /// The square of the rook that castles.
+    Short(Square),
+    Long(Square),
 }
 
 impl Castle {
@@ -206,18 +207,37 @@ impl Castle {
         S: AsRef<str>,
         C: Into<Colour> + Copy,
     {
-        let row: i8 = match colour.into() {
-            Colour::White => 0,
-            Colour::Black => 7,
+        let rank = match colour.into() {
+            Colour::White => Rank::FIRST,
+            Colour::Black => Rank::EIGHTH,
         };
-        match value.as_ref().chars().nth(0).unwrap() {
-            'O' if value.as_ref().len() <= 3 => Castle::Short([7, row]),
-            'O' if value.as_ref().len() > 3 => Castle::Long([0, row]),
+        match value.as_ref().trim_end_matches(['+', '#']) {
+            "O-O" => Castle::Short(Square::new(File::H, rank)),
+            "O-O-O" => Castle::Long(Square::new(File::A, rank)),
             _ => Castle::No,
         }
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingRights {
+    pub white_short: bool,
+    pub white_long: bool,
+    pub black_short: bool,
+    pub black_long: bool,
+}
+
+impl CastlingRights {
+    pub fn all() -> Self {
+        Self {
+            white_short: true,
+            white_long: true,
+            black_short: true,
+            black_long: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Capture {
     Yes,
@@ -243,26 +263,93 @@ pub enum Promotion {
 }
 
 impl Promotion {
+    /// Reads the promotion at the end of a pawn move, written either as "e8=Q" or "e8Q".
     pub fn from_notation<S, C>(value: S, colour: C) -> Self
     where
         S: AsRef<str>,
         C: Into<Colour> + Copy,
     {
-        let mut piece = None;
+        let mut chars = value.as_ref().trim_end_matches(['+', '#']).chars().rev();
 
-        for symbol in "KQBNR".chars() {
-            if let Some(pos) = value
-                .as_ref()
-                .find(|s: char| s.is_uppercase() && s == symbol)
+        match (chars.next(), chars.next()) {
+            (Some(symbol), Some(before))
+                if "QRBN".contains(symbol) && (before == '=' || before.is_ascii_digit()) =>
             {
-                if pos == 3 {
-                    piece = Some(PieceType::from_char_and_colour(symbol, colour))
-                };
+                Self::Yes(PieceType::from_char_and_colour(symbol, colour))
             }
+            _ => Self::No,
         }
-        match piece {
-            Some(p) => Self::Yes(p),
-            None => Self::No,
+    }
+
+    /// The notation with the promotion part taken out, so "exd8=Q+" becomes "exd8+".
+    pub fn strip_notation<S>(self, value: S) -> String
+    where
+        S: AsRef<str>,
+    {
+        let value = value.as_ref();
+
+        match self {
+            Self::No => value.to_string(),
+            Self::Yes(_) => {
+                let body = value.trim_end_matches(['+', '#']);
+                let suffix = &value[body.len()..];
+
+                format!("{}{suffix}", body[..body.len() - 1].trim_end_matches('='))
+            }
+        }
+    }
+}
+
+/// How a game ended on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The given colour delivered mate.
+    Checkmate(Colour),
+    Stalemate,
+    /// A draw the rules impose without either player claiming it.
+    Draw(DrawRule),
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Checkmate(winner) => write!(f, "Checkmate, {winner:?} wins"),
+            Self::Stalemate => write!(f, "Draw by stalemate"),
+            Self::Draw(rule) => write!(f, "Draw by {rule}"),
+        }
+    }
+}
+
+/// A rule that draws the game. Threefold repetition and the fifty-move rule let a player
+/// claim the draw, the others end the game by themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawRule {
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    FiftyMoves,
+    SeventyFiveMoves,
+    /// Neither side has the pieces left to mate.
+    InsufficientMaterial,
+}
+
+impl DrawRule {
+    /// Whether the game ends without a claim.
+    pub fn is_automatic(self) -> bool {
+        matches!(
+            self,
+            Self::FivefoldRepetition | Self::SeventyFiveMoves | Self::InsufficientMaterial
+        )
+    }
+}
+
+impl Display for DrawRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ThreefoldRepetition => write!(f, "threefold repetition"),
+            Self::FivefoldRepetition => write!(f, "fivefold repetition"),
+            Self::FiftyMoves => write!(f, "the fifty-move rule"),
+            Self::SeventyFiveMoves => write!(f, "the seventy-five-move rule"),
+            Self::InsufficientMaterial => write!(f, "insufficient material"),
         }
     }
 }
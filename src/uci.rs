@@ -0,0 +1,146 @@
+use crate::{
+    board::Board,
+    error::SjakkError,
+    movegen::ResolvedMove,
+    square::{File, Square},
+    turn::Move,
+    types::{Castle, PieceType, Promotion},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        square::Rank,
+        types::{Capture, Colour},
+    };
+
+    #[test]
+    fn test_parse_uci() {
+        let board = Board::default();
+        let mv = Move::new_from_uci("g1f3", &board).unwrap();
+
+        assert_eq!(mv.piece, PieceType::Knight(Colour::White));
+        assert_eq!(mv.from, (Some(File::G), Some(Rank::FIRST)));
+        assert_eq!(mv.to, Some("f3".parse().unwrap()));
+        assert_eq!(mv.to_uci().unwrap(), "g1f3");
+
+        assert!(Move::new_from_uci("e2e5", &board).is_err());
+        assert!(Move::new_from_uci("e2", &board).is_err());
+        assert!(Move::new_from_uci("e2e4x", &board).is_err());
+    }
+
+    #[test]
+    fn test_special_moves() {
+        let board = Board::from_fen("r2n1k1r/4P3/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+
+        let castle = Move::new_from_uci("e1g1", &board).unwrap();
+        assert_eq!(castle.castle, Castle::Short("h1".parse().unwrap()));
+        assert_eq!(castle.to_string(), "O-O+");
+        assert_eq!(castle.to_uci().unwrap(), "e1g1");
+
+        let promotion = board.parse_uci("e7e8q").unwrap();
+        assert_eq!(
+            promotion.promotion,
+            Promotion::Yes(PieceType::Queen(Colour::White))
+        );
+        assert_eq!(promotion.to_uci(), "e7e8q");
+        assert!(board.parse_uci("e7e8").is_err());
+
+        let capture = board.parse_uci("e7d8n").unwrap();
+        assert_eq!(capture.capture, Capture::Yes);
+        assert_eq!(board.san(&capture).to_string(), "exd8=N");
+    }
+
+    #[test]
+    fn test_san_without_squares() {
+        let mv = Move::new_from_notation("Nf3", 'w').unwrap();
+        assert_eq!(mv.to_uci(), None);
+    }
+}
+
+impl Board {
+    /// Finds the legal move written in UCI long algebraic notation, e.g. "e2e4", "e7e8q" or "e1g1".
+    pub fn parse_uci<S>(&self, value: S) -> Result<ResolvedMove, SjakkError>
+    where
+        S: AsRef<str>,
+    {
+        let value = value.as_ref();
+        let invalid = || SjakkError::parse(value, "not a UCI move");
+
+        let square = |range| {
+            value
+                .get(range)
+                .and_then(|s: &str| s.parse::<Square>().ok())
+                .ok_or_else(invalid)
+        };
+        let from = square(0..2)?;
+        let to = square(2..4)?;
+        let promotion = match value.get(4..) {
+            Some("") => None,
+            Some(symbol @ ("q" | "r" | "b" | "n")) => Some(PieceType::from_notation(
+                symbol.to_uppercase(),
+                self.side_to_move,
+            )?),
+            _ => return Err(invalid()),
+        };
+
+        self.find_move(from, to, promotion)
+            .ok_or_else(|| SjakkError::IllegalMove {
+                notation: value.to_string(),
+                reason: "no legal move goes between these squares".to_string(),
+                ply: None,
+            })
+    }
+}
+
+impl ResolvedMove {
+    /// The move in UCI long algebraic notation.
+    pub fn to_uci(&self) -> String {
+        uci(self.from, self.to, self.promotion)
+    }
+}
+
+impl Move {
+    /// Reads a move in UCI long algebraic notation, played on `board`.
+    ///
+    /// Unlike a move read from SAN, `from` always holds the full starting square.
+    pub fn new_from_uci<S>(notation: S, board: &Board) -> Result<Self, SjakkError>
+    where
+        S: AsRef<str>,
+    {
+        let resolved = board.parse_uci(notation)?;
+
+        Ok(Self {
+            from: (Some(resolved.from.file()), Some(resolved.from.rank())),
+            ..board.san(&resolved)
+        })
+    }
+
+    /// The move in UCI long algebraic notation, when the notation it was read from
+    /// gave the full starting square.
+    pub fn to_uci(&self) -> Option<String> {
+        let (from, to) = match self.castle {
+            Castle::Short(rook) => (
+                Square::new(File::E, rook.rank()),
+                Square::new(File::G, rook.rank()),
+            ),
+            Castle::Long(rook) => (
+                Square::new(File::E, rook.rank()),
+                Square::new(File::C, rook.rank()),
+            ),
+            Castle::No => (Square::new(self.from.0?, self.from.1?), self.to?),
+        };
+
+        Some(uci(from, to, self.promotion))
+    }
+}
+
+fn uci(from: Square, to: Square, promotion: Promotion) -> String {
+    let promotion = match promotion {
+        Promotion::Yes(piece) => format!("{piece:?}").to_lowercase(),
+        Promotion::No => String::new(),
+    };
+
+    format!("{from}{to}{promotion}")
+}